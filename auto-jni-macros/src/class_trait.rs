@@ -0,0 +1,440 @@
+//! `#[java_class("java/util/ArrayList")]` on a trait: a wrapper for a
+//! class with only the methods declared in the trait.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Expr, ExprLit, FnArg, GenericArgument, ItemTrait, Lit, Meta, Pat, PathArguments, ReturnType, TraitItem,
+    TraitItemFn, Type,
+};
+
+/// A descriptor, known at compile time or built from a wrapper's
+/// `JavaObject::CLASS` on first use.
+#[derive(Clone)]
+enum Descriptor {
+    Lit(String),
+    Class(Type),
+}
+
+impl Descriptor {
+    fn expr(&self) -> TokenStream2 {
+        match self {
+            Descriptor::Lit(lit) => quote! { #lit },
+            Descriptor::Class(ty) => quote! { &format!("L{};", <#ty as auto_jni::JavaObject>::CLASS) },
+        }
+    }
+}
+
+/// How a Rust type crosses into Java.
+enum Kind {
+    Primitive(&'static str, &'static str),
+    Str,
+    Object(Descriptor),
+    Void,
+}
+
+/// The wrapper struct for `class`, with an inherent method per trait item.
+pub fn expand(class: &str, item: &ItemTrait) -> syn::Result<TokenStream2> {
+    let class = class.replace('.', "/");
+    let ItemTrait { attrs, vis, ident, .. } = item;
+    let display = class.replace('/', ".");
+
+    let mut methods = Vec::new();
+    for item in &item.items {
+        match item {
+            TraitItem::Fn(method) => methods.push(expand_method(&class, method)?),
+            other => return Err(syn::Error::new_spanned(other, "only methods can be bound")),
+        }
+    }
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #ident {
+            inner: auto_jni::jni::objects::GlobalRef,
+        }
+
+        impl auto_jni::JavaObject for #ident {
+            const CLASS: &'static str = #class;
+            fn from_global(inner: auto_jni::jni::objects::GlobalRef) -> Self {
+                Self { inner }
+            }
+            fn inner(&self) -> &auto_jni::jni::objects::GlobalRef {
+                &self.inner
+            }
+        }
+
+        impl Clone for #ident {
+            fn clone(&self) -> Self {
+                Self { inner: auto_jni::java().new_global_ref(&self.inner).unwrap() }
+            }
+        }
+
+        impl std::fmt::Debug for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, concat!(#display, "@{:x}"), auto_jni::object::identity_hash_code(self.inner.as_obj()))
+            }
+        }
+
+        impl std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&auto_jni::object::to_string(self.inner.as_obj()).map_err(|_| std::fmt::Error)?)
+            }
+        }
+
+        impl PartialEq for #ident {
+            fn eq(&self, other: &Self) -> bool {
+                auto_jni::object::equals(self.inner.as_obj(), other.inner.as_obj())
+            }
+        }
+
+        impl Eq for #ident {}
+
+        impl std::hash::Hash for #ident {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                std::hash::Hash::hash(&auto_jni::object::hash_code(self.inner.as_obj()), state)
+            }
+        }
+
+        impl #ident {
+            #(#methods)*
+        }
+    })
+}
+
+fn expand_method(class: &str, method: &TraitItemFn) -> syn::Result<TokenStream2> {
+    if let Some(body) = &method.default {
+        return Err(syn::Error::new_spanned(body, "bound methods are declared without a body"));
+    }
+    let sig = &method.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() || sig.variadic.is_some() {
+        return Err(syn::Error::new_spanned(sig, "bound methods can't be generic, async or variadic"));
+    }
+
+    // `#[java_name = "toString"]` binds a Java name that isn't the Rust one,
+    // and `#[constructor]` binds a constructor under a name other than `new`.
+    let mut java_name = sig.ident.to_string();
+    let mut constructor_attr = None;
+    let mut docs = Vec::new();
+    for attr in &method.attrs {
+        if attr.path().is_ident("constructor") {
+            attr.meta.require_path_only()?;
+            constructor_attr = Some(attr);
+        } else if attr.path().is_ident("java_name") {
+            match &attr.meta {
+                Meta::NameValue(nv) => match &nv.value {
+                    Expr::Lit(ExprLit { lit: Lit::Str(name), .. }) => java_name = name.value(),
+                    value => return Err(syn::Error::new_spanned(value, "expected a string")),
+                },
+                meta => return Err(syn::Error::new_spanned(meta, "expected `#[java_name = \"...\"]`")),
+            }
+        } else {
+            docs.push(attr);
+        }
+    }
+
+    let mut has_self = false;
+    let mut params = Vec::new();
+    let mut descriptors = Vec::new();
+    let mut conversions = Vec::new();
+    let mut values = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(arg) = input else {
+            let FnArg::Receiver(receiver) = input else { unreachable!() };
+            if receiver.reference.is_none() || receiver.mutability.is_some() {
+                return Err(syn::Error::new_spanned(receiver, "bound methods take `&self`"));
+            }
+            has_self = true;
+            continue;
+        };
+        let name = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.clone(),
+            _ => format_ident!("arg_{}", i),
+        };
+        let ty = &arg.ty;
+        params.push(quote! { #name: #ty });
+        match arg_kind(ty)? {
+            Kind::Primitive(descriptor, _) => {
+                descriptors.push(Descriptor::Lit(descriptor.to_string()));
+                values.push(quote! { auto_jni::jni::objects::JValue::from(#name).as_jni() });
+            }
+            Kind::Str => {
+                descriptors.push(Descriptor::Lit("Ljava/lang/String;".to_string()));
+                conversions.push(quote! {
+                    let #name = __env.new_string(#name)?;
+                    let #name = __env.auto_local(#name);
+                });
+                values.push(quote! { auto_jni::jni::objects::JValue::Object(&#name).as_jni() });
+            }
+            Kind::Object(descriptor) => {
+                // `&GlobalRef` and `&AutoLocal` deref to `&JObject`.
+                let obj = match &descriptor {
+                    Descriptor::Lit(_) => quote! { #name },
+                    Descriptor::Class(_) => quote! { auto_jni::JavaObject::inner(#name) },
+                };
+                descriptors.push(descriptor);
+                values.push(quote! { auto_jni::jni::objects::JValue::Object(#obj).as_jni() });
+            }
+            Kind::Void => return Err(syn::Error::new_spanned(ty, "`()` isn't a parameter type")),
+        }
+    }
+
+    let declared = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(result_ok_type(ty)),
+    };
+    let is_constructor = constructor_attr.is_some() || (sig.ident == "new" && !has_self);
+    if is_constructor && (has_self || !declared.is_some_and(is_self)) {
+        let span = constructor_attr.map_or_else(|| quote! { #sig }, |attr| quote! { #attr });
+        return Err(syn::Error::new_spanned(span, "constructors are declared without `self` and return `Self`"));
+    }
+    let ret = match declared {
+        None => Kind::Void,
+        Some(_) if is_constructor => Kind::Void,
+        Some(ty) => match return_kind(ty)? {
+            // Other functions returning `Self` are static factories.
+            Kind::Object(Descriptor::Class(ty)) if is_self(&ty) => Kind::Object(Descriptor::Lit(format!("L{};", class))),
+            kind => kind,
+        },
+    };
+    let ret_descriptor = match &ret {
+        Kind::Void => Descriptor::Lit("V".to_string()),
+        Kind::Primitive(descriptor, _) => Descriptor::Lit(descriptor.to_string()),
+        Kind::Str => Descriptor::Lit("Ljava/lang/String;".to_string()),
+        Kind::Object(descriptor) => descriptor.clone(),
+    };
+
+    // A literal when every part is known at compile time.
+    descriptors.push(ret_descriptor);
+    let signature = if descriptors.iter().all(|d| matches!(d, Descriptor::Lit(_))) {
+        let parts: Vec<&str> = descriptors.iter().map(|d| match d {
+            Descriptor::Lit(lit) => lit.as_str(),
+            Descriptor::Class(_) => unreachable!(),
+        }).collect();
+        let (ret, args) = parts.split_last().unwrap();
+        let signature = format!("({}){}", args.concat(), ret);
+        quote! { #signature }
+    } else {
+        let (ret, args) = descriptors.split_last().unwrap();
+        let args = args.iter().map(Descriptor::expr);
+        let ret = ret.expr();
+        quote! { { &["(", #(#args,)* ")", #ret].concat() } }
+    };
+
+    let ident = &sig.ident;
+    if is_constructor {
+        return Ok(quote! {
+            #(#docs)*
+            pub fn #ident(#(#params),*) -> Result<Self, auto_jni::errors::JNIError> {
+                let mut __env = auto_jni::java();
+                #(#conversions)*
+                Ok(Self {
+                    inner: auto_jni::create!(env = &mut __env, #class, #signature, &[#(#values),*]),
+                })
+            }
+        });
+    }
+
+    let return_type = match &ret {
+        Kind::Void => quote! { () },
+        _ => {
+            let ty = result_ok_type(match &sig.output {
+                ReturnType::Type(_, ty) => ty,
+                ReturnType::Default => unreachable!(),
+            });
+            quote! { #ty }
+        }
+    };
+    let jni_return = match &ret {
+        Kind::Void => quote! { auto_jni::jni::signature::ReturnType::Primitive(auto_jni::jni::signature::Primitive::Void) },
+        Kind::Primitive(_, primitive) => {
+            let primitive = format_ident!("{}", primitive);
+            quote! { auto_jni::jni::signature::ReturnType::Primitive(auto_jni::jni::signature::Primitive::#primitive) }
+        }
+        Kind::Str | Kind::Object(_) => quote! { auto_jni::jni::signature::ReturnType::Object },
+    };
+    let call = if has_self {
+        quote! { auto_jni::call!(env = &mut __env, self.inner.as_obj(), #class, #java_name, #signature, &[#(#values),*], #jni_return) }
+    } else {
+        quote! { auto_jni::call_static!(env = &mut __env, #class, #java_name, #signature, &[#(#values),*], #jni_return) }
+    };
+    let convert = match &ret {
+        Kind::Void => quote! { Ok(()) },
+        Kind::Primitive("Z", _) => quote! { Ok(__result.z()?) },
+        Kind::Primitive("C", _) => quote! { Ok(__result.c()?) },
+        Kind::Primitive("B", _) => quote! { Ok(__result.b()?) },
+        Kind::Primitive("S", _) => quote! { Ok(__result.s()?) },
+        Kind::Primitive("I", _) => quote! { Ok(__result.i()?) },
+        Kind::Primitive("J", _) => quote! { Ok(__result.j()?) },
+        Kind::Primitive("F", _) => quote! { Ok(__result.f()?) },
+        Kind::Primitive(_, _) => quote! { Ok(__result.d()?) },
+        Kind::Str | Kind::Object(_) => quote! {
+            let __obj = __result.l()?;
+            <#return_type as auto_jni::FromJava>::from_java(&mut __env, __obj)
+        },
+    };
+    let receiver = if has_self { quote! { &self, } } else { quote! {} };
+    Ok(quote! {
+        #(#docs)*
+        pub fn #ident(#receiver #(#params),*) -> Result<#return_type, auto_jni::errors::JNIError> {
+            let mut __env = auto_jni::java();
+            #(#conversions)*
+            let __result = #call;
+            #convert
+        }
+    })
+}
+
+/// `T` for a declared `Result<T, _>`, the type itself otherwise.
+fn result_ok_type(ty: &Type) -> &Type {
+    if let Some(args) = last_segment_args(ty, "Result") {
+        if let Some(GenericArgument::Type(ok)) = args.first() {
+            return ok;
+        }
+    }
+    ty
+}
+
+fn last_segment_args<'a>(ty: &'a Type, name: &str) -> Option<Vec<&'a GenericArgument>> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => Some(args.args.iter().collect()),
+        _ => None,
+    }
+}
+
+fn is_self(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("Self"))
+}
+
+fn last_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn primitive(ty: &Type) -> Option<Kind> {
+    let kind = match last_ident(ty)?.as_str() {
+        "bool" => Kind::Primitive("Z", "Boolean"),
+        "i8" => Kind::Primitive("B", "Byte"),
+        "u16" => Kind::Primitive("C", "Char"),
+        "i16" => Kind::Primitive("S", "Short"),
+        "i32" => Kind::Primitive("I", "Int"),
+        "i64" => Kind::Primitive("J", "Long"),
+        "f32" => Kind::Primitive("F", "Float"),
+        "f64" => Kind::Primitive("D", "Double"),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Parameters: primitives, `&str`, `&JObject`, `&GlobalRef`, or a
+/// reference to another wrapper.
+fn arg_kind(ty: &Type) -> syn::Result<Kind> {
+    if let Some(kind) = primitive(ty) {
+        return Ok(kind);
+    }
+    if let Type::Tuple(tuple) = ty {
+        if tuple.elems.is_empty() {
+            return Ok(Kind::Void);
+        }
+    }
+    let Type::Reference(reference) = ty else {
+        return Err(syn::Error::new_spanned(ty, "expected a primitive, `&str`, `&JObject`, `&GlobalRef` or a reference to a wrapper"));
+    };
+    let inner = &*reference.elem;
+    Ok(match last_ident(inner).as_deref() {
+        Some("str") => Kind::Str,
+        Some("JObject" | "GlobalRef" | "AutoLocal") => Kind::Object(Descriptor::Lit("Ljava/lang/Object;".to_string())),
+        _ => Kind::Object(Descriptor::Class(inner.clone())),
+    })
+}
+
+/// Results: primitives, `String`, `AutoLocal`/`GlobalRef` (as `Object`),
+/// another wrapper, or an `Option` of one of those for nullable results.
+fn return_kind(ty: &Type) -> syn::Result<Kind> {
+    if let Some(kind) = primitive(ty) {
+        return Ok(kind);
+    }
+    if let Type::Tuple(tuple) = ty {
+        if tuple.elems.is_empty() {
+            return Ok(Kind::Void);
+        }
+    }
+    if let Some(args) = last_segment_args(ty, "Option") {
+        if let Some(GenericArgument::Type(inner)) = args.first() {
+            return match return_kind(inner)? {
+                Kind::Str | Kind::Object(_) => return_kind(inner),
+                _ => Err(syn::Error::new_spanned(ty, "only object results can be `Option`")),
+            };
+        }
+    }
+    Ok(match last_ident(ty).as_deref() {
+        Some("String") => Kind::Str,
+        Some("AutoLocal" | "GlobalRef") => Kind::Object(Descriptor::Lit("Ljava/lang/Object;".to_string())),
+        Some(_) => Kind::Object(Descriptor::Class(ty.clone())),
+        None => return Err(syn::Error::new_spanned(ty, "unsupported result type")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_str(declaration: &str) -> syn::Result<String> {
+        expand("java.util.ArrayList", &syn::parse_str(declaration)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn test_constructors_and_factories() {
+        let expanded = expand_str(
+            "trait ArrayList {
+                #[constructor]
+                fn with_capacity(capacity: i32) -> Self;
+                #[java_name = \"valueOf\"]
+                fn value_of(i: i32) -> Self;
+                fn copy(other: &ArrayList) -> Option<Self>;
+            }",
+        )
+        .unwrap();
+        assert!(expanded.contains("pub fn with_capacity (capacity : i32) -> Result < Self , auto_jni :: errors :: JNIError >"));
+        assert!(expanded.contains("create ! (env = & mut __env , \"java/util/ArrayList\" , \"(I)V\""));
+        // Only constructors bind `<init>`; the rest are static calls.
+        assert!(expanded.contains("call_static ! (env = & mut __env , \"java/util/ArrayList\" , \"valueOf\" , \"(I)Ljava/util/ArrayList;\""));
+        assert!(expanded.contains("pub fn value_of (i : i32) -> Result < Self , auto_jni :: errors :: JNIError >"));
+        assert!(expanded.contains("\"copy\" , { & [\"(\" , & format ! (\"L{};\" , < ArrayList as auto_jni :: JavaObject > :: CLASS) , \")\" , \"Ljava/util/ArrayList;\"] . concat () }"));
+        assert!(!expanded.contains("# [constructor]"));
+    }
+
+    #[test]
+    fn test_signatures() {
+        let expanded = expand_str(
+            "trait ArrayList {
+                fn new(capacity: i32) -> Self;
+                fn add(&self, index: i32, o: &JObject) -> ();
+                fn contains(&self, s: &str) -> Result<bool, JNIError>;
+                #[java_name = \"subList\"]
+                fn sub_list(&self, from: i32, to: i32) -> Option<ArrayList>;
+            }",
+        )
+        .unwrap();
+        assert!(expanded.contains("const CLASS : & 'static str = \"java/util/ArrayList\""));
+        assert!(expanded.contains("create ! (env = & mut __env , \"java/util/ArrayList\" , \"(I)V\""));
+        assert!(expanded.contains("\"add\" , \"(ILjava/lang/Object;)V\""));
+        assert!(expanded.contains("\"contains\" , \"(Ljava/lang/String;)Z\""));
+        assert!(expanded.contains("pub fn contains (& self , s : & str) -> Result < bool , auto_jni :: errors :: JNIError >"));
+        // Wrapper types take their descriptor from `JavaObject::CLASS`.
+        assert!(expanded.contains("\"subList\" , { & [\"(\" , \"I\" , \"I\" , \")\" , & format ! (\"L{};\" , < ArrayList as auto_jni :: JavaObject > :: CLASS)] . concat () }"));
+
+        assert!(expand_str("trait A { fn f(&mut self); }").is_err());
+        assert!(expand_str("trait A { #[constructor] fn f(&self) -> Self; }").is_err());
+        assert!(expand_str("trait A { #[constructor] fn f() -> i32; }").is_err());
+        assert!(expand_str("trait A { fn new() -> i32; }").is_err());
+        assert!(expand_str("trait A { fn f(&self) {} }").is_err());
+        assert!(expand_str("trait A { fn f(&self, v: Vec<u8>); }").is_err());
+    }
+}
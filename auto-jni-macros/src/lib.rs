@@ -0,0 +1,220 @@
+//! Bind Java classes inline with auto-jni, without a `build.rs`.
+//!
+//! Both macros run the same `javap` parsing and code generation as
+//! `auto_jni::generate_bindings_file`, at compile time:
+//!
+//! ```ignore
+//! auto_jni_macros::bind!("com.example.Car", classpath = "java/classes");
+//!
+//! #[auto_jni_macros::java_class("com.example.Car", classpath = "java/classes")]
+//! mod car {}
+//! ```
+//!
+//! Class path entries are separated as in `CLASSPATH` (`:`, or `;` on
+//! Windows) and relative ones are relative to the crate's `Cargo.toml`.
+//! The crate is rebuilt when a class file javap read for the bindings or a
+//! jar on the class path changes. Unless `jvm_options` is given, the JVM
+//! is started with the class path as `-Djava.class.path`. The generated items include a `java()` function
+//! and `JVM_OPTIONS`, so bind every class a module needs with one macro.
+
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, parse_macro_input, Ident, ItemMod, ItemTrait, LitBool, LitStr, Token};
+
+mod class_trait;
+
+/// Expand to the bindings for the given classes, in place.
+///
+/// Takes class names, then `classpath = "..."`, and optionally
+/// `jvm_options = ["..."]` and `rust_char = true` (see
+/// `auto_jni::BindingOptions`).
+#[proc_macro]
+pub fn bind(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as Args);
+    expand(&args).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// On an inline module, add the bindings for the given classes to it.
+/// Takes the same arguments as [`bind!`].
+///
+/// On a trait, bind only the methods it declares, with JNI signatures
+/// derived from their Rust types. The trait becomes a wrapper struct of
+/// the same name with an inherent method per declaration:
+///
+/// ```ignore
+/// #[java_class("java/util/ArrayList")]
+/// pub trait ArrayList {
+///     fn new() -> Self;
+///     fn add(&self, o: &JObject) -> bool;
+///     fn size(&self) -> i32;
+///     #[java_name = "toString"]
+///     fn describe(&self) -> String;
+/// }
+/// ```
+///
+/// Methods without `self` are static, except `new` and those marked
+/// `#[constructor]`, which bind a constructor and return `Self`. Other
+/// static methods returning `Self` are factories, such as
+/// `#[java_name = "valueOf"] fn value_of(i: i32) -> Self`. Parameters can be primitives, `&str`,
+/// `&JObject`, `&GlobalRef` (both passed as `Object`) or references to
+/// other wrappers; results can be primitives, `String`, `AutoLocal` or
+/// `GlobalRef` (as `Object`), other wrappers, or an `Option` of an object
+/// type when Java may return `null`. Every method returns
+/// `Result<T, JNIError>`. The JVM has to be running already: started with
+/// `auto_jni::vm::init`, or the JVM that loaded the library.
+#[proc_macro_attribute]
+pub fn java_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    if let Ok(declaration) = syn::parse::<ItemTrait>(item.clone()) {
+        if args.classes.len() != 1 || args.classpath.is_some() || args.jvm_options.is_some() || args.rust_char {
+            return syn::Error::new(args.classes[0].span(), "a bound trait takes only a class name")
+                .into_compile_error()
+                .into();
+        }
+        return class_trait::expand(&args.classes[0].value(), &declaration)
+            .unwrap_or_else(syn::Error::into_compile_error)
+            .into();
+    }
+    let module = parse_macro_input!(item as ItemMod);
+    let Some((_, items)) = &module.content else {
+        return syn::Error::new_spanned(&module, "`#[java_class]` needs an inline module: `mod name {}`")
+            .into_compile_error()
+            .into();
+    };
+
+    let bindings = match expand(&args) {
+        Ok(bindings) => bindings,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let ItemMod { attrs, vis, ident, .. } = &module;
+    quote! {
+        #(#attrs)*
+        #vis mod #ident {
+            #bindings
+            #(#items)*
+        }
+    }
+    .into()
+}
+
+#[derive(Default)]
+struct Args {
+    classes: Vec<LitStr>,
+    classpath: Option<LitStr>,
+    jvm_options: Option<Vec<LitStr>>,
+    rust_char: bool,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args::default();
+        while !input.is_empty() {
+            if input.peek(LitStr) {
+                args.classes.push(input.parse()?);
+            } else {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                match key.to_string().as_str() {
+                    "classes" => args.classes.extend(string_list(input)?),
+                    "classpath" => args.classpath = Some(input.parse()?),
+                    "jvm_options" => args.jvm_options = Some(string_list(input)?),
+                    "rust_char" => args.rust_char = input.parse::<LitBool>()?.value,
+                    _ => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "expected a class name, `classes`, `classpath`, `jvm_options` or `rust_char`",
+                        ))
+                    }
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        if args.classes.is_empty() {
+            return Err(input.error("expected at least one class name"));
+        }
+        Ok(args)
+    }
+}
+
+/// `["a", "b"]`
+fn string_list(input: ParseStream) -> syn::Result<Vec<LitStr>> {
+    let content;
+    bracketed!(content in input);
+    Ok(Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?.into_iter().collect())
+}
+
+fn expand(args: &Args) -> syn::Result<TokenStream2> {
+    let span = args.classes[0].span();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    // Each class path entry is relative to the manifest on its own.
+    let entries: Vec<PathBuf> = args.classpath.as_ref()
+        .map(|cp| std::env::split_paths(&cp.value()).map(|entry| Path::new(&manifest_dir).join(entry)).collect())
+        .unwrap_or_default();
+    let classpath = match &args.classpath {
+        Some(cp) => Some(
+            std::env::join_paths(&entries)
+                .map_err(|err| syn::Error::new(cp.span(), format!("invalid class path: {}", err)))?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        None => None,
+    };
+    let jvm_options = match &args.jvm_options {
+        Some(options) => Some(options.iter().map(LitStr::value).collect()),
+        None => classpath.as_ref().map(|cp| vec![format!("-Djava.class.path={}", cp)]),
+    };
+    let classes: Vec<String> = args.classes.iter().map(LitStr::value).collect();
+    let options = auto_jni::BindingOptions { rust_char: args.rust_char };
+
+    let (bindings, sources) = auto_jni::generate_bindings_with_sources(
+        classes.iter().map(String::as_str).collect(),
+        classpath.clone(),
+        jvm_options,
+        &options,
+    )
+    .map_err(|err| syn::Error::new(span, format!("failed to generate bindings: {}", err)))?;
+    let bindings: TokenStream2 = bindings.parse()
+        .map_err(|err| syn::Error::new(span, format!("generated bindings don't parse: {}", err)))?;
+
+    // Rebuild when a class file javap read or a jar on the class path
+    // changes. Directories can't be tracked.
+    let tracked = sources.iter()
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let path = LitStr::new(&path.display().to_string(), Span::call_site());
+            quote! { const _: &[u8] = include_bytes!(#path); }
+        });
+
+    Ok(quote! {
+        #(#tracked)*
+        #bindings
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args: Args = syn::parse_str(r#""com.example.Car", "com.example.Engine", classpath = "java", rust_char = true"#).unwrap();
+        assert_eq!(args.classes.iter().map(LitStr::value).collect::<Vec<_>>(), ["com.example.Car", "com.example.Engine"]);
+        assert_eq!(args.classpath.unwrap().value(), "java");
+        assert!(args.jvm_options.is_none());
+        assert!(args.rust_char);
+
+        let args: Args = syn::parse_str(r#"classes = ["com.example.Car"], jvm_options = ["-Xmx64m"],"#).unwrap();
+        assert_eq!(args.classes.len(), 1);
+        assert_eq!(args.jvm_options.unwrap()[0].value(), "-Xmx64m");
+
+        assert!(syn::parse_str::<Args>(r#"classpath = "java""#).is_err());
+        assert!(syn::parse_str::<Args>(r#""com.example.Car", class_path = "java""#).is_err());
+    }
+}
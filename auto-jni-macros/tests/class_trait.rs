@@ -0,0 +1,54 @@
+//! Trait bindings whose parameters share names with the generated code's
+//! locals (`env`, `result`, `obj`).
+
+use auto_jni_macros::java_class;
+
+#[java_class("java.lang.StringBuilder")]
+pub trait StringBuilder {
+    fn new(env: &str) -> Self;
+    #[java_name = "indexOf"]
+    fn index_of(&self, env: &str, obj: i32) -> i32;
+    #[java_name = "charAt"]
+    fn char_at(&self, result: i32) -> u16;
+    #[java_name = "toString"]
+    fn text(&self) -> String;
+}
+
+#[java_class("java.lang.Integer")]
+pub trait Integer {
+    #[java_name = "toString"]
+    fn to_string_radix(env: i32, result: i32) -> String;
+    #[java_name = "valueOf"]
+    fn value_of(i: i32) -> Self;
+    #[constructor]
+    fn boxed(value: i32) -> Self;
+    #[java_name = "intValue"]
+    fn int_value(&self) -> i32;
+}
+
+#[test]
+fn test_parameters_named_like_locals() {
+    auto_jni::vm::get_or_init(&[]).unwrap();
+
+    let builder = StringBuilder::new("env, obj, env").unwrap();
+    assert_eq!(builder.text().unwrap(), "env, obj, env");
+    assert_eq!(builder.index_of("env", 1).unwrap(), 10);
+    assert_eq!(builder.char_at(5).unwrap(), 'o' as u16);
+    assert_eq!(Integer::to_string_radix(255, 16).unwrap(), "ff");
+}
+
+#[test]
+fn test_static_factory() {
+    auto_jni::vm::get_or_init(&[]).unwrap();
+
+    // `valueOf` returns the cached instance; only `#[constructor]` makes a new one.
+    let cached = Integer::value_of(42).unwrap();
+    assert_eq!(cached.int_value().unwrap(), 42);
+    assert_eq!(cached.to_string(), "42");
+    let env = auto_jni::java();
+    assert!(env.is_same_object(Integer::value_of(42).unwrap().inner.as_obj(), cached.inner.as_obj()).unwrap());
+
+    let boxed = Integer::boxed(42).unwrap();
+    assert!(!env.is_same_object(boxed.inner.as_obj(), cached.inner.as_obj()).unwrap());
+    assert_eq!(boxed, cached);
+}
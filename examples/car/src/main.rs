@@ -8,4 +8,8 @@ fn main() {
     let car = com_example_Car::new(&make, &model, 2024, &car_type).unwrap();
 
     car.displayInfo().unwrap();
+
+    let same_car = car.clone();
+    println!("{:?} == {:?}: {}", car, same_car, car == same_car);
+    println!("{}", car);
 }
@@ -0,0 +1,29 @@
+package autojni;
+
+import java.util.function.BiConsumer;
+
+/**
+ * Forwards a CompletableFuture's completion to Rust.
+ *
+ * Defined at runtime by auto_jni from the bytes of NativeCallback.class; the
+ * handle is a pointer owned by the Rust side and released by complete().
+ * Rebuild with: javac --release 8 autojni/NativeCallback.java
+ */
+final class NativeCallback implements BiConsumer<Object, Throwable> {
+    private long handle;
+
+    NativeCallback(long handle) {
+        this.handle = handle;
+    }
+
+    @Override
+    public synchronized void accept(Object result, Throwable error) {
+        long h = handle;
+        handle = 0;
+        if (h != 0) {
+            complete(h, result, error);
+        }
+    }
+
+    private static native void complete(long handle, Object result, Throwable error);
+}
@@ -0,0 +1,136 @@
+//! Java arrays built from Rust slices, used for varargs parameters.
+
+use jni::objects::{AutoLocal, JObject, JObjectArray, JPrimitiveArray, JValue};
+use jni::JNIEnv;
+
+use crate::errors::JNIError;
+use crate::java;
+use crate::object::IntoJava;
+
+type ObjectArray = AutoLocal<'static, JObjectArray<'static>>;
+
+/// `String[]` from string slices.
+pub fn string_array(items: &[&str]) -> Result<ObjectArray, JNIError> {
+    let mut env = java();
+    let array = new_object_array(&mut env, "java/lang/String", items.len())?;
+    for (i, item) in items.iter().enumerate() {
+        let item = env.auto_local(env.new_string(item)?);
+        env.set_object_array_element(&array, i as i32, &item)?;
+    }
+    Ok(array)
+}
+
+/// An array of `class` (e.g. `java/lang/Runnable`) holding `items`.
+pub fn object_array(class: &str, items: &[&JObject]) -> Result<ObjectArray, JNIError> {
+    let mut env = java();
+    let array = new_object_array(&mut env, class, items.len())?;
+    for (i, item) in items.iter().enumerate() {
+        env.set_object_array_element(&array, i as i32, item)?;
+    }
+    Ok(array)
+}
+
+/// `Object[]` from `JValue`s, boxing primitives the way Java varargs do.
+pub fn boxed_array(items: &[JValue]) -> Result<ObjectArray, JNIError> {
+    let mut env = java();
+    let array = new_object_array(&mut env, "java/lang/Object", items.len())?;
+    for (i, item) in items.iter().enumerate() {
+        let boxed = match *item {
+            JValue::Object(obj) => env.new_local_ref(obj)?,
+            JValue::Byte(v) => v.into_java(&mut env)?,
+            JValue::Char(v) => v.into_java(&mut env)?,
+            JValue::Short(v) => v.into_java(&mut env)?,
+            JValue::Int(v) => v.into_java(&mut env)?,
+            JValue::Long(v) => v.into_java(&mut env)?,
+            JValue::Bool(v) => (v != 0).into_java(&mut env)?,
+            JValue::Float(v) => v.into_java(&mut env)?,
+            JValue::Double(v) => v.into_java(&mut env)?,
+            JValue::Void => return Err(JNIError::InvalidArg),
+        };
+        let boxed = env.auto_local(boxed);
+        env.set_object_array_element(&array, i as i32, &boxed)?;
+    }
+    Ok(array)
+}
+
+fn new_object_array(env: &mut JNIEnv<'static>, class: &str, len: usize) -> Result<ObjectArray, JNIError> {
+    let array = env.new_object_array(len as i32, class, JObject::null())?;
+    Ok(env.auto_local(array))
+}
+
+macro_rules! primitive_array {
+    ($name:ident, $ty:ty, $jty:ty, $new:ident, $set:ident) => {
+        #[doc = concat!("`", stringify!($ty), "` slice as a Java primitive array.")]
+        pub fn $name(items: &[$ty]) -> Result<AutoLocal<'static, JPrimitiveArray<'static, $jty>>, JNIError> {
+            let env = java();
+            let array = env.auto_local(env.$new(items.len() as i32)?);
+            env.$set(&*array, 0, items)?;
+            Ok(array)
+        }
+    };
+}
+
+primitive_array!(int_array, i32, jni::sys::jint, new_int_array, set_int_array_region);
+primitive_array!(long_array, i64, jni::sys::jlong, new_long_array, set_long_array_region);
+primitive_array!(double_array, f64, jni::sys::jdouble, new_double_array, set_double_array_region);
+primitive_array!(float_array, f32, jni::sys::jfloat, new_float_array, set_float_array_region);
+primitive_array!(byte_array, i8, jni::sys::jbyte, new_byte_array, set_byte_array_region);
+primitive_array!(short_array, i16, jni::sys::jshort, new_short_array, set_short_array_region);
+primitive_array!(char_array, u16, jni::sys::jchar, new_char_array, set_char_array_region);
+
+/// `bool` slice as a Java `boolean[]`.
+pub fn boolean_array(items: &[bool]) -> Result<AutoLocal<'static, JPrimitiveArray<'static, jni::sys::jboolean>>, JNIError> {
+    let env = java();
+    let items: Vec<u8> = items.iter().map(|&b| b as u8).collect();
+    let array = env.auto_local(env.new_boolean_array(items.len() as i32)?);
+    env.set_boolean_array_region(&*array, 0, &items)?;
+    Ok(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use jni::signature::ReturnType;
+
+    use super::*;
+    use crate::call_static;
+    use crate::object::FromJava;
+
+    #[test]
+    fn test_format_varargs() {
+        let mut env = crate::vm::test_jvm().env();
+        let format = env.new_string("%s=%d %b").unwrap();
+        let name = env.new_string("x").unwrap();
+        let args = boxed_array(&[JValue::Object(&name), JValue::Int(42), JValue::Bool(1)]).unwrap();
+
+        let formatted = call_static!(
+            env = &mut env,
+            "java/lang/String",
+            "format",
+            "(Ljava/lang/String;[Ljava/lang/Object;)Ljava/lang/String;",
+            &[JValue::Object(&format).as_jni(), JValue::Object(&args).as_jni()],
+            ReturnType::Object
+        );
+        let formatted = String::from_java(&mut env, formatted.l().unwrap()).unwrap();
+        assert_eq!(formatted, "x=42 true");
+    }
+
+    #[test]
+    fn test_arrays() {
+        let mut env = crate::vm::test_jvm().env();
+
+        let strings = string_array(&["a", "b"]).unwrap();
+        assert_eq!(env.get_array_length(&*strings).unwrap(), 2);
+        let second = env.get_object_array_element(&*strings, 1).unwrap();
+        assert_eq!(String::from_java(&mut env, second).unwrap(), "b");
+
+        let ints = int_array(&[1, 2, 3]).unwrap();
+        let mut read = [0; 3];
+        env.get_int_array_region(&*ints, 0, &mut read).unwrap();
+        assert_eq!(read, [1, 2, 3]);
+
+        let flags = boolean_array(&[true, false]).unwrap();
+        let mut read = [0; 2];
+        env.get_boolean_array_region(&*flags, 0, &mut read).unwrap();
+        assert_eq!(read, [1, 0]);
+    }
+}
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    auto_jni::cli::main()
+}
@@ -0,0 +1,268 @@
+//! `java.nio.ByteBuffer` interop.
+//!
+//! Rust bytes are lent to Java as a direct `ByteBuffer` for the duration of
+//! a call with [`with_borrowed`], and buffers coming back from Java are read
+//! and written through [`JavaBytes`].
+
+use jni::objects::{AutoLocal, GlobalRef, JByteBuffer, JObject, JValue};
+use jni::signature::{Primitive, ReturnType};
+use jni::JNIEnv;
+
+use crate::errors::JNIError;
+use crate::{call, call_static, java};
+
+/// Run `f` with `bytes` lent to Java as a direct `ByteBuffer`.
+///
+/// The buffer's memory is allocated by Java (`ByteBuffer.allocateDirect`),
+/// not borrowed from `bytes`: they are copied in before `f` runs and the
+/// buffer's whole contents are copied back when it returns. Java code may
+/// therefore keep the buffer; later writes to it just aren't seen by Rust.
+/// Fails with `JNIError::InvalidArg` if `bytes` is longer than a Java
+/// buffer can be.
+pub fn with_borrowed<T>(bytes: &mut [u8], f: impl FnOnce(&JObject<'static>) -> T) -> Result<T, JNIError> {
+    let len = i32::try_from(bytes.len()).map_err(|_| JNIError::InvalidArg)?;
+    let mut env = java();
+    let buffer = call_static!(
+        env = &mut env,
+        "java/nio/ByteBuffer",
+        "allocateDirect",
+        "(I)Ljava/nio/ByteBuffer;",
+        &[JValue::Int(len).as_jni()],
+        ReturnType::Object
+    );
+    let buffer = env.auto_local(JByteBuffer::from(buffer.l()?));
+    if !bytes.is_empty() {
+        // Java hasn't seen the buffer yet, so nothing else touches its memory.
+        let address = env.get_direct_buffer_address(&buffer)?;
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), address, bytes.len()) };
+    }
+
+    let result = f(&buffer);
+
+    // Java may still hold the buffer and write to it, so copy back through
+    // its own methods rather than its memory.
+    let duplicate = call!(
+        env = &mut env,
+        &buffer,
+        "java/nio/ByteBuffer",
+        "duplicate",
+        "()Ljava/nio/ByteBuffer;",
+        &[],
+        ReturnType::Object
+    );
+    let duplicate = env.auto_local(duplicate.l()?);
+    env.call_method(&duplicate, "clear", "()Ljava/nio/Buffer;", &[])?;
+    let array = env.auto_local(env.new_byte_array(len)?);
+    env.call_method(&duplicate, "get", "([B)Ljava/nio/ByteBuffer;", &[JValue::Object(&array)])?;
+    bytes.copy_from_slice(&env.convert_byte_array(&*array)?);
+    Ok(result)
+}
+
+/// The remaining bytes (`position..limit`) of a `ByteBuffer` from Java.
+///
+/// Java may still read and write the buffer, so safe access goes through
+/// copies: [`to_vec`](Self::to_vec) copies the bytes out and
+/// [`write`](Self::write) copies bytes in, both through the buffer's own
+/// methods. Direct buffers can also be borrowed in place with the `unsafe`
+/// [`as_slice`](Self::as_slice) and [`as_mut_slice`](Self::as_mut_slice);
+/// the `GlobalRef` keeps the buffer, and so its memory, alive for as long
+/// as this value.
+pub struct JavaBytes {
+    buffer: GlobalRef,
+    position: usize,
+    len: usize,
+    /// The buffer's memory at `position`, for direct buffers.
+    direct: Option<*mut u8>,
+}
+
+// The pointer is into memory owned by the buffer held in `buffer`, and is
+// only dereferenced by the `unsafe` accessors, under their contract.
+unsafe impl Send for JavaBytes {}
+unsafe impl Sync for JavaBytes {}
+
+impl JavaBytes {
+    pub fn new(buffer: &JObject) -> Result<Self, JNIError> {
+        let mut env = java();
+        let buffer = env.new_global_ref(buffer)?;
+        let position = int_call(buffer.as_obj(), "position")? as usize;
+        let len = int_call(buffer.as_obj(), "remaining")? as usize;
+
+        let is_direct = call!(
+            env = &mut env,
+            buffer.as_obj(),
+            "java/nio/ByteBuffer",
+            "isDirect",
+            "()Z",
+            &[],
+            ReturnType::Primitive(Primitive::Boolean)
+        );
+        let direct = if is_direct.z()? {
+            let address = env.get_direct_buffer_address(<&JByteBuffer>::from(buffer.as_obj()))?;
+            Some(unsafe { address.add(position) })
+        } else {
+            None
+        };
+
+        Ok(Self { buffer, position, len, direct })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the buffer is direct, so its memory can be borrowed in place.
+    pub fn is_direct(&self) -> bool {
+        self.direct.is_some()
+    }
+
+    /// Copy the bytes out of the buffer.
+    pub fn to_vec(&self) -> Result<Vec<u8>, JNIError> {
+        let mut env = java();
+        let duplicate = self.duplicate_at(&mut env, 0)?;
+        let array = env.auto_local(env.new_byte_array(self.len as i32)?);
+        env.call_method(&duplicate, "get", "([B)Ljava/nio/ByteBuffer;", &[JValue::Object(&array)])?;
+        Ok(env.convert_byte_array(&*array)?)
+    }
+
+    /// Copy `bytes` into the buffer, starting `offset` bytes into this
+    /// value. Fails with `JNIError::InvalidArg` if they don't fit, and with
+    /// the Java exception if the buffer is read-only.
+    pub fn write(&self, offset: usize, bytes: &[u8]) -> Result<(), JNIError> {
+        if offset > self.len || bytes.len() > self.len - offset {
+            return Err(JNIError::InvalidArg);
+        }
+        let mut env = java();
+        let duplicate = self.duplicate_at(&mut env, offset)?;
+        let array = env.auto_local(env.byte_array_from_slice(bytes)?);
+        env.call_method(&duplicate, "put", "([B)Ljava/nio/ByteBuffer;", &[JValue::Object(&array)])?;
+        Ok(())
+    }
+
+    /// The bytes of a direct buffer, in place; `None` for heap buffers.
+    ///
+    /// # Safety
+    ///
+    /// Nothing may write to the bytes while the slice is alive: no Java
+    /// code, and no [`as_mut_slice`](Self::as_mut_slice) of another
+    /// `JavaBytes` over the same buffer.
+    pub unsafe fn as_slice(&self) -> Option<&[u8]> {
+        match self.direct {
+            Some(_) if self.len == 0 => Some(&[]),
+            Some(ptr) => Some(std::slice::from_raw_parts(ptr, self.len)),
+            None => None,
+        }
+    }
+
+    /// The bytes of a direct buffer, in place and writable; `None` for heap
+    /// buffers. Writes are visible to Java.
+    ///
+    /// # Safety
+    ///
+    /// Nothing else may read or write the bytes while the slice is alive:
+    /// no Java code, and no slice from another `JavaBytes` over the same
+    /// buffer.
+    pub unsafe fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        match self.direct {
+            Some(_) if self.len == 0 => Some(&mut []),
+            Some(ptr) => Some(std::slice::from_raw_parts_mut(ptr, self.len)),
+            None => None,
+        }
+    }
+
+    pub fn inner(&self) -> &GlobalRef {
+        &self.buffer
+    }
+
+    /// A duplicate of the buffer positioned `offset` bytes into this value,
+    /// so the caller's position is untouched.
+    fn duplicate_at(
+        &self,
+        env: &mut JNIEnv<'static>,
+        offset: usize,
+    ) -> Result<AutoLocal<'static, JObject<'static>>, JNIError> {
+        let duplicate = call!(
+            env = env,
+            self.buffer.as_obj(),
+            "java/nio/ByteBuffer",
+            "duplicate",
+            "()Ljava/nio/ByteBuffer;",
+            &[],
+            ReturnType::Object
+        );
+        let duplicate = env.auto_local(duplicate.l()?);
+        let position = JValue::Int((self.position + offset) as i32);
+        env.call_method(&duplicate, "position", "(I)Ljava/nio/Buffer;", &[position])?;
+        Ok(duplicate)
+    }
+}
+
+fn int_call(buffer: &JObject, method: &str) -> Result<i32, JNIError> {
+    let mut env = java();
+    Ok(env.call_method(buffer, method, "()I", &[])?.i()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lend_rust_memory() {
+        let mut env = crate::vm::test_jvm().env();
+        let mut bytes = [1u8, 2, 3, 4];
+        let kept = with_borrowed(&mut bytes, |buffer| {
+            let first = env.call_method(buffer, "get", "()B", &[]).unwrap().b().unwrap();
+            assert_eq!(first, 1);
+            env.call_method(buffer, "put", "(IB)Ljava/nio/ByteBuffer;", &[JValue::Int(2), JValue::Byte(7)])
+                .unwrap();
+            env.new_global_ref(buffer).unwrap()
+        })
+        .unwrap();
+        assert_eq!(bytes, [1, 2, 7, 4]);
+
+        // Java keeping the buffer doesn't reach back into Rust memory.
+        env.call_method(&kept, "put", "(IB)Ljava/nio/ByteBuffer;", &[JValue::Int(0), JValue::Byte(9)])
+            .unwrap();
+        assert_eq!(bytes, [1, 2, 7, 4]);
+
+        assert_eq!(with_borrowed(&mut [], |_| 5).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_direct_and_heap_buffers() {
+        let mut env = crate::vm::test_jvm().env();
+
+        let direct = env
+            .call_static_method("java/nio/ByteBuffer", "allocateDirect", "(I)Ljava/nio/ByteBuffer;", &[JValue::Int(4)])
+            .unwrap()
+            .l()
+            .unwrap();
+        let mut bytes = JavaBytes::new(&direct).unwrap();
+        assert!(bytes.is_direct());
+        unsafe { bytes.as_mut_slice().unwrap()[1] = 9 };
+        let read = env.call_method(&direct, "get", "(I)B", &[JValue::Int(1)]).unwrap().b().unwrap();
+        assert_eq!(read, 9);
+        bytes.write(2, &[5, 6]).unwrap();
+        assert_eq!(bytes.to_vec().unwrap(), [0, 9, 5, 6]);
+        assert_eq!(unsafe { bytes.as_slice() }, Some(&[0, 9, 5, 6][..]));
+        assert!(matches!(bytes.write(3, &[1, 2]), Err(JNIError::InvalidArg)));
+
+        let array = env.byte_array_from_slice(&[1, 2, 3, 4]).unwrap();
+        let heap = env
+            .call_static_method("java/nio/ByteBuffer", "wrap", "([B)Ljava/nio/ByteBuffer;", &[JValue::Object(&array)])
+            .unwrap()
+            .l()
+            .unwrap();
+        env.call_method(&heap, "position", "(I)Ljava/nio/ByteBuffer;", &[JValue::Int(1)]).unwrap();
+        let bytes = JavaBytes::new(&heap).unwrap();
+        assert!(!bytes.is_direct());
+        assert_eq!(unsafe { bytes.as_slice() }, None);
+        assert_eq!(bytes.to_vec().unwrap(), [2, 3, 4]);
+        bytes.write(1, &[8]).unwrap();
+        assert_eq!(env.convert_byte_array(&array).unwrap(), [1, 2, 8, 4]);
+        assert_eq!(int_call(&heap, "position").unwrap(), 1);
+    }
+}
@@ -1,50 +1,373 @@
-/// Call a static Java method, caching the method ID in a `OnceCell`.
+/// Call a static Java method, caching the method ID and a global reference to
+/// the class in `OnceCell`s.
+///
+/// A string literal signature is checked at compile time against the
+/// arguments and return type (see [`signature`](crate::signature)).
+///
+/// Pass `env = &mut env` as the first argument to use an explicit `JNIEnv`;
+/// otherwise the env comes from the registered [`Jvm`](crate::Jvm).
+///
+/// A class name missing its `;` doesn't compile:
+/// ```compile_fail
+/// # use auto_jni::call_static;
+/// # use auto_jni::jni::objects::JValue;
+/// # use auto_jni::jni::signature::{Primitive, ReturnType};
+/// let name = auto_jni::java().new_string("calc").unwrap();
+/// call_static!(
+///     "java/lang/System",
+///     "loadLibrary",
+///     "(Ljava/lang/String)V",
+///     &[JValue::Object(&name).as_jni()],
+///     ReturnType::Primitive(Primitive::Void)
+/// );
+/// ```
+///
+/// Nor does passing the wrong number of arguments:
+/// ```compile_fail
+/// # use auto_jni::call_static;
+/// # use auto_jni::jni::objects::JValue;
+/// # use auto_jni::jni::signature::{Primitive, ReturnType};
+/// call_static!("java/lang/Math", "max", "(II)I", &[JValue::Int(2).as_jni()], ReturnType::Primitive(Primitive::Int));
+/// ```
 #[macro_export]
 macro_rules! call_static {
-    ($path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {{
-        use auto_jni::once_cell::sync::OnceCell;
-        use auto_jni::jni::objects::{JClass, JStaticMethodID};
-        use crate::java;
+    (env = $env:expr, $path:tt, $method:tt, $sig:literal, &[$($arg:expr),* $(,)?], $($ret:tt)+) => {{
+        const _: () = $crate::signature::check_method(
+            $sig,
+            Some(<[&str]>::len(&[$(stringify!($arg)),*])),
+            $crate::__const_return_type!($($ret)+),
+        );
+        $crate::call_static!(@unchecked $env, $path, $method, $sig, &[$($arg),*], $($ret)+)
+    }};
+    (env = $env:expr, $path:tt, $method:tt, $sig:literal, $args:expr, $($ret:tt)+) => {{
+        const _: () = $crate::signature::check_method($sig, None, $crate::__const_return_type!($($ret)+));
+        $crate::call_static!(@unchecked $env, $path, $method, $sig, $args, $($ret)+)
+    }};
+    (env = $env:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {
+        $crate::call_static!(@unchecked $env, $path, $method, $sig, $args, $ret)
+    };
+    (@unchecked $env:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {{
+        use $crate::once_cell::sync::OnceCell;
+        use $crate::jni::objects::{GlobalRef, JClass, JStaticMethodID};
         static FNPTR: OnceCell<JStaticMethodID> = OnceCell::new();
-        static CLASS: OnceCell<JClass> = OnceCell::new();
-        let mut env = java();
-        let fnptr = FNPTR.get_or_init(|| env.get_static_method_id($path, $method, $sig).unwrap());
-        let class = CLASS.get_or_init(|| env.find_class($path).unwrap());
-        unsafe { env.call_static_method_unchecked(class, fnptr, $ret, $args).unwrap() }
+        static CLASS: OnceCell<GlobalRef> = OnceCell::new();
+        let env: &mut $crate::jni::JNIEnv = $env;
+        let (ret, args) = ($ret, $args);
+        let class = CLASS.get_or_init(|| {
+            let local = env.find_class($path).unwrap();
+            let global = env.new_global_ref(&local).unwrap();
+            env.delete_local_ref(local).unwrap();
+            global
+        });
+        let class: &JClass = class.as_obj().into();
+        let fnptr = FNPTR.get_or_init(|| env.get_static_method_id(class, $method, $sig).unwrap());
+        unsafe { env.call_static_method_unchecked(class, fnptr, ret, args).unwrap() }
+    }};
+    ($path:tt, $method:tt, $sig:tt, &[$($arg:expr),* $(,)?], $($ret:tt)+) => {{
+        let mut env = $crate::java();
+        $crate::call_static!(env = &mut env, $path, $method, $sig, &[$($arg),*], $($ret)+)
+    }};
+    ($path:tt, $method:tt, $sig:tt, $args:expr, $($ret:tt)+) => {{
+        let mut env = $crate::java();
+        $crate::call_static!(env = &mut env, $path, $method, $sig, $args, $($ret)+)
     }};
 }
 
 /// Call an instance Java method, caching the method ID in a `OnceCell`.
+/// Literal signatures are checked at compile time, as for [`call_static!`].
+///
+/// Pass `env = &mut env` as the first argument to use an explicit `JNIEnv`;
+/// otherwise the env comes from the registered [`Jvm`](crate::Jvm).
+///
+/// A class name missing its `;` doesn't compile:
+/// ```compile_fail
+/// # use auto_jni::call;
+/// # use auto_jni::jni::objects::JValue;
+/// # use auto_jni::jni::signature::{Primitive, ReturnType};
+/// let mut env = auto_jni::java();
+/// let list = env.new_object("java/util/ArrayList", "()V", &[]).unwrap();
+/// let item = env.new_string("item").unwrap();
+/// call!(
+///     env = &mut env,
+///     &list,
+///     "java/util/List",
+///     "add",
+///     "(ILjava/lang/Object)V",
+///     &[JValue::Int(0).as_jni(), JValue::Object(&item).as_jni()],
+///     ReturnType::Primitive(Primitive::Void)
+/// );
+/// ```
+///
+/// Nor does passing the wrong number of arguments:
+/// ```compile_fail
+/// # use auto_jni::call;
+/// # use auto_jni::jni::objects::JValue;
+/// # use auto_jni::jni::signature::{Primitive, ReturnType};
+/// let mut env = auto_jni::java();
+/// let list = env.new_object("java/util/ArrayList", "()V", &[]).unwrap();
+/// call!(env = &mut env, &list, "java/util/List", "size", "()I", &[JValue::Int(0).as_jni()], ReturnType::Primitive(Primitive::Int));
+/// ```
 #[macro_export]
 macro_rules! call {
-    ($obj:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {{
-        use auto_jni::once_cell::sync::OnceCell;
-        use auto_jni::jni::objects::JMethodID;
-        use crate::java;
+    (env = $env:expr, $obj:expr, $path:tt, $method:tt, $sig:literal, &[$($arg:expr),* $(,)?], $($ret:tt)+) => {{
+        const _: () = $crate::signature::check_method(
+            $sig,
+            Some(<[&str]>::len(&[$(stringify!($arg)),*])),
+            $crate::__const_return_type!($($ret)+),
+        );
+        $crate::call!(@unchecked $env, $obj, $path, $method, $sig, &[$($arg),*], $($ret)+)
+    }};
+    (env = $env:expr, $obj:expr, $path:tt, $method:tt, $sig:literal, $args:expr, $($ret:tt)+) => {{
+        const _: () = $crate::signature::check_method($sig, None, $crate::__const_return_type!($($ret)+));
+        $crate::call!(@unchecked $env, $obj, $path, $method, $sig, $args, $($ret)+)
+    }};
+    (env = $env:expr, $obj:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {
+        $crate::call!(@unchecked $env, $obj, $path, $method, $sig, $args, $ret)
+    };
+    (@unchecked $env:expr, $obj:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $ret:expr) => {{
+        use $crate::once_cell::sync::OnceCell;
+        use $crate::jni::objects::JMethodID;
         static FNPTR: OnceCell<JMethodID> = OnceCell::new();
-        let mut env = java();
+        let env: &mut $crate::jni::JNIEnv = $env;
+        let (obj, ret, args) = ($obj, $ret, $args);
         let fnptr = FNPTR.get_or_init(|| {
             let class = env.find_class($path).unwrap();
-            env.get_method_id(class, $method, $sig).unwrap()
+            let id = env.get_method_id(&class, $method, $sig).unwrap();
+            env.delete_local_ref(class).unwrap();
+            id
         });
-        unsafe { env.call_method_unchecked($obj, fnptr, $ret, $args).unwrap() }
+        unsafe { env.call_method_unchecked(obj, fnptr, ret, args).unwrap() }
+    }};
+    ($obj:expr, $path:tt, $method:tt, $sig:tt, &[$($arg:expr),* $(,)?], $($ret:tt)+) => {{
+        let mut env = $crate::java();
+        $crate::call!(env = &mut env, $obj, $path, $method, $sig, &[$($arg),*], $($ret)+)
+    }};
+    ($obj:expr, $path:tt, $method:tt, $sig:tt, $args:expr, $($ret:tt)+) => {{
+        let mut env = $crate::java();
+        $crate::call!(env = &mut env, $obj, $path, $method, $sig, $args, $($ret)+)
     }};
 }
 
-/// Construct a Java object, caching the constructor ID in a `OnceCell`.
-/// Returns a `GlobalRef`.
+/// Construct a Java object, caching the constructor ID and a global reference
+/// to the class in `OnceCell`s.
+/// Returns a `GlobalRef`. Literal signatures are checked at compile time,
+/// as for [`call_static!`].
+///
+/// Pass `env = &mut env` as the first argument to use an explicit `JNIEnv`;
+/// otherwise the env comes from the registered [`Jvm`](crate::Jvm).
+///
+/// A class name missing its `;` doesn't compile:
+/// ```compile_fail
+/// # use auto_jni::create;
+/// # use auto_jni::jni::objects::JValue;
+/// let text = auto_jni::java().new_string("text").unwrap();
+/// create!("java/lang/StringBuilder", "(Ljava/lang/String)V", &[JValue::Object(&text).as_jni()]);
+/// ```
+///
+/// Nor does passing the wrong number of arguments:
+/// ```compile_fail
+/// # use auto_jni::create;
+/// # use auto_jni::jni::objects::JValue;
+/// create!("java/lang/StringBuilder", "(I)V", &[]);
+/// ```
 #[macro_export]
 macro_rules! create {
-    ($path:tt, $sig:tt, $args:expr) => {{
-        use auto_jni::once_cell::sync::OnceCell;
-        use auto_jni::jni::objects::{JClass, JMethodID};
-        use crate::java;
+    (env = $env:expr, $path:tt, $sig:literal, &[$($arg:expr),* $(,)?]) => {{
+        const _: () = $crate::signature::check_constructor($sig, Some(<[&str]>::len(&[$(stringify!($arg)),*])));
+        $crate::create!(@unchecked $env, $path, $sig, &[$($arg),*])
+    }};
+    (env = $env:expr, $path:tt, $sig:literal, $args:expr) => {{
+        const _: () = $crate::signature::check_constructor($sig, None);
+        $crate::create!(@unchecked $env, $path, $sig, $args)
+    }};
+    (env = $env:expr, $path:tt, $sig:tt, $args:expr) => {
+        $crate::create!(@unchecked $env, $path, $sig, $args)
+    };
+    (@unchecked $env:expr, $path:tt, $sig:tt, $args:expr) => {{
+        use $crate::once_cell::sync::OnceCell;
+        use $crate::jni::objects::{GlobalRef, JClass, JMethodID};
         static FNPTR: OnceCell<JMethodID> = OnceCell::new();
-        static CLASS: OnceCell<JClass> = OnceCell::new();
-        let mut env = java();
-        let class = CLASS.get_or_init(|| env.find_class($path).unwrap());
+        static CLASS: OnceCell<GlobalRef> = OnceCell::new();
+        let env: &mut $crate::jni::JNIEnv = $env;
+        let args = $args;
+        let class = CLASS.get_or_init(|| {
+            let local = env.find_class($path).unwrap();
+            let global = env.new_global_ref(&local).unwrap();
+            env.delete_local_ref(local).unwrap();
+            global
+        });
+        let class: &JClass = class.as_obj().into();
         let fnptr = FNPTR.get_or_init(|| env.get_method_id(class, "<init>", $sig).unwrap());
-        let obj = unsafe { env.new_object_unchecked(class, *fnptr, $args).unwrap() };
-        env.new_global_ref(obj).unwrap()
+        let obj = unsafe { env.new_object_unchecked(class, *fnptr, args).unwrap() };
+        let global = env.new_global_ref(&obj).unwrap();
+        env.delete_local_ref(obj).unwrap();
+        global
     }};
+    ($path:tt, $sig:tt, &[$($arg:expr),* $(,)?]) => {{
+        let mut env = $crate::java();
+        $crate::create!(env = &mut env, $path, $sig, &[$($arg),*])
+    }};
+    ($path:tt, $sig:tt, $args:expr) => {{
+        let mut env = $crate::java();
+        $crate::create!(env = &mut env, $path, $sig, $args)
+    }};
+}
+
+/// The `ReturnType` passed to `call!` or `call_static!`, for checking in a
+/// `const`, when it is written out as a path like `ReturnType::Object` or
+/// `ReturnType::Primitive(Primitive::Int)`. Anything else may depend on
+/// variables, so it is only checked at run time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __const_return_type {
+    (@primitive Primitive :: $p:ident) => {
+        Some($crate::jni::signature::ReturnType::Primitive($crate::jni::signature::Primitive::$p))
+    };
+    (@primitive $(::)? $segment:ident :: $($rest:tt)+) => {
+        $crate::__const_return_type!(@primitive $($rest)+)
+    };
+    (@primitive $($ret:tt)*) => {
+        None
+    };
+    (ReturnType :: Object) => {
+        Some($crate::jni::signature::ReturnType::Object)
+    };
+    (ReturnType :: Array) => {
+        Some($crate::jni::signature::ReturnType::Array)
+    };
+    (ReturnType :: Primitive ($($primitive:tt)+)) => {
+        $crate::__const_return_type!(@primitive $($primitive)+)
+    };
+    // Drop the leading segments of a qualified path.
+    ($(::)? $segment:ident :: $($rest:tt)+) => {
+        $crate::__const_return_type!($($rest)+)
+    };
+    ($($ret:tt)+) => {
+        None
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use jni::objects::JValue;
+    use jni::signature::{Primitive, ReturnType};
+    use jni::JNIEnv;
+
+    #[test]
+    fn test_explicit_env() {
+        let mut env = crate::vm::test_jvm().env();
+
+        let abs = call_static!(
+            env = &mut env,
+            "java/lang/Math",
+            "abs",
+            "(I)I",
+            &[JValue::Int(-3).as_jni()],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        assert_eq!(abs.i().unwrap(), 3);
+
+        let s = env.new_string("hello").unwrap();
+        let builder = create!(
+            env = &mut env,
+            "java/lang/StringBuilder",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(&s).as_jni()]
+        );
+        let len = call!(
+            env = &mut env,
+            builder.as_obj(),
+            "java/lang/StringBuilder",
+            "length",
+            "()I",
+            &[],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        assert_eq!(len.i().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_registered_jvm() {
+        crate::vm::test_jvm();
+
+        let max = call_static!(
+            "java/lang/Math",
+            "max",
+            "(II)I",
+            &[JValue::Int(2).as_jni(), JValue::Int(7).as_jni()],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        assert_eq!(max.i().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_runtime_return_type() {
+        let mut env = crate::vm::test_jvm().env();
+
+        // Only known when the call runs, so it isn't checked in a `const`.
+        let primitive = if env.get_version().is_ok() { Primitive::Long } else { Primitive::Int };
+        let abs = call_static!(
+            env = &mut env,
+            "java/lang/Math",
+            "abs",
+            "(J)J",
+            &[JValue::Long(-3).as_jni()],
+            ReturnType::Primitive(primitive)
+        );
+        assert_eq!(abs.j().unwrap(), 3);
+
+        let ret = ReturnType::Object;
+        let s = env.new_string("hello").unwrap();
+        let upper = call!(env = &mut env, &s, "java/lang/String", "toUpperCase", "()Ljava/lang/String;", &[], ret);
+        let upper: String = env.get_string(&upper.l().unwrap().into()).unwrap().into();
+        assert_eq!(upper, "HELLO");
+
+        // Fully qualified paths are still checked.
+        let max = call_static!(
+            env = &mut env,
+            "java/lang/Math",
+            "max",
+            "(II)I",
+            &[JValue::Int(2).as_jni(), JValue::Int(7).as_jni()],
+            jni::signature::ReturnType::Primitive(jni::signature::Primitive::Int)
+        );
+        assert_eq!(max.i().unwrap(), 7);
+    }
+
+    fn parse_int(env: &mut JNIEnv, s: &str) -> i32 {
+        env.with_local_frame(4, |env| {
+            let s = env.new_string(s)?;
+            let parsed = call_static!(
+                env = env,
+                "java/lang/Integer",
+                "parseInt",
+                "(Ljava/lang/String;)I",
+                &[JValue::Object(&s).as_jni()],
+                ReturnType::Primitive(Primitive::Int)
+            );
+            parsed.i()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_class_cache_outlives_frames_and_threads() {
+        let jvm = crate::vm::test_jvm();
+
+        // The first call caches the class inside a frame that is popped again.
+        assert_eq!(parse_int(&mut jvm.env(), "1"), 1);
+        assert_eq!(parse_int(&mut jvm.env(), "2"), 2);
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let mut env = jvm.env();
+                    (0..16).map(|j| parse_int(&mut env, &(i * 100 + j).to_string())).sum::<i32>()
+                })
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), (0..16).map(|j| i as i32 * 100 + j).sum());
+        }
+    }
 }
@@ -0,0 +1,94 @@
+//! Java `char`, `char[]` and `CharSequence` as Rust text.
+//!
+//! A Java `char` is a UTF-16 code unit, not a Unicode scalar value: the
+//! conversions to and from Rust `char` fail for surrogates and for
+//! characters outside the Basic Multilingual Plane.
+
+use jni::objects::{AutoLocal, JCharArray, JObject, JString};
+
+use crate::errors::JNIError;
+use crate::{array, java};
+
+/// A Java `char` as a Rust `char`. Surrogates have no `char` of their own.
+pub fn to_char(unit: u16) -> Result<char, JNIError> {
+    char::from_u32(unit as u32).ok_or(JNIError::InvalidChar(unit as u32))
+}
+
+/// A Rust `char` as a Java `char`, if it fits in one UTF-16 code unit.
+pub fn to_java_char(c: char) -> Result<u16, JNIError> {
+    u16::try_from(c as u32).map_err(|_| JNIError::InvalidChar(c as u32))
+}
+
+/// The UTF-16 code units of a `char[]`.
+pub fn utf16_from_array(array: &JObject) -> Result<Vec<u16>, JNIError> {
+    if array.is_null() {
+        return Err(JNIError::NullPtr);
+    }
+    let env = java();
+    let array = <&JCharArray>::from(array);
+    let mut units = vec![0; env.get_array_length(array)? as usize];
+    env.get_char_array_region(array, 0, &mut units)?;
+    Ok(units)
+}
+
+/// A `char[]` decoded as UTF-16. Unpaired surrogates are an error.
+pub fn string_from_array(array: &JObject) -> Result<String, JNIError> {
+    char::decode_utf16(utf16_from_array(array)?)
+        .map(|c| c.map_err(|err| JNIError::InvalidChar(err.unpaired_surrogate() as u32)))
+        .collect()
+}
+
+/// A `char[]` holding the UTF-16 encoding of `s`.
+pub fn array_from_str(s: &str) -> Result<AutoLocal<'static, JCharArray<'static>>, JNIError> {
+    array::char_array(&s.encode_utf16().collect::<Vec<_>>())
+}
+
+/// A `java.lang.String` to pass where a `CharSequence` is expected.
+pub fn char_sequence(s: &str) -> Result<AutoLocal<'static, JString<'static>>, JNIError> {
+    let env = java();
+    Ok(env.auto_local(env.new_string(s)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use jni::objects::JValue;
+    use jni::signature::{Primitive, ReturnType};
+
+    use super::*;
+    use crate::call;
+
+    #[test]
+    fn test_char_conversions() {
+        assert_eq!(to_char(0x41).unwrap(), 'A');
+        assert_eq!(to_char(0x00e9).unwrap(), 'é');
+        assert!(matches!(to_char(0xd83d), Err(JNIError::InvalidChar(0xd83d))));
+        assert_eq!(to_java_char('é').unwrap(), 0x00e9);
+        assert!(matches!(to_java_char('🦀'), Err(JNIError::InvalidChar(0x1f980))));
+    }
+
+    #[test]
+    fn test_char_arrays() {
+        let mut env = crate::vm::test_jvm().env();
+
+        let array = array_from_str("hé🦀").unwrap();
+        assert_eq!(utf16_from_array(&array).unwrap().len(), 4);
+        assert_eq!(string_from_array(&array).unwrap(), "hé🦀");
+
+        let broken = array::char_array(&[0x68, 0xdc00]).unwrap();
+        assert!(matches!(string_from_array(&broken), Err(JNIError::InvalidChar(0xdc00))));
+
+        let text = char_sequence("crab").unwrap();
+        let len = call!(
+            env = &mut env,
+            &text,
+            "java/lang/CharSequence",
+            "length",
+            "()I",
+            &[],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        assert_eq!(len.i().unwrap(), 4);
+        let last = env.call_method(&text, "charAt", "(I)C", &[JValue::Int(3)]).unwrap().c().unwrap();
+        assert_eq!(to_char(last).unwrap(), 'b');
+    }
+}
@@ -0,0 +1,312 @@
+//! The `auto-jni` command, for generating bindings without a build script
+//! (build with `--features cli`):
+//!
+//! ```text
+//! auto-jni generate --class com.example.Car --classpath java/classes --out src/bindings.rs
+//! auto-jni check --class com.example.Car --classpath java/classes --out src/bindings.rs
+//! auto-jni list --class com.example.Car --classpath java/classes
+//! auto-jni inspect --class com.example.Car --classpath java/classes
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::codegen::{describe_class, generate_bindings, verify_bindings_file, write_bindings_file, BindingOptions};
+use crate::{parse_javap_output, JavaClass, MethodBinding};
+
+const USAGE: &str = "\
+Usage: auto-jni <COMMAND> --class <CLASS>... [OPTIONS]
+
+Commands:
+  generate  Write Rust bindings for the classes
+  check     Fail if the bindings in --out are out of date with the classes
+  list      Show the Rust items each class would be bound to
+  inspect   Print the methods parsed from each class as JSON
+
+Options:
+  --class <CLASS>       Fully-qualified class name, e.g. com.example.Car (repeatable)
+  --classpath <PATH>    Class path passed to javap
+  --out <FILE>          The bindings file [default for `generate`: stdout]
+  --jvm-option <OPT>    JVM option for the generated JVM_OPTIONS (repeatable)
+                        [default: -Djava.class.path=<PATH>]
+  --rust-char           Map Java char to Rust char (see BindingOptions)
+  -h, --help            Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Generate,
+    Check,
+    List,
+    Inspect,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    classes: Vec<String>,
+    classpath: Option<String>,
+    out: Option<String>,
+    jvm_options: Vec<String>,
+    rust_char: bool,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let command = match args.next().map(String::as_str) {
+            Some("generate") => Command::Generate,
+            Some("check") => Command::Check,
+            Some("list") => Command::List,
+            Some("inspect") => Command::Inspect,
+            Some("help" | "-h" | "--help") => Command::Help,
+            Some(other) => return Err(format!("unknown command `{}`", other)),
+            None => return Err("missing command".to_string()),
+        };
+
+        let mut parsed = Args {
+            command,
+            classes: Vec::new(),
+            classpath: None,
+            out: None,
+            jvm_options: Vec::new(),
+            rust_char: false,
+        };
+        if command == Command::Help {
+            return Ok(parsed);
+        }
+        let generates = matches!(command, Command::Generate | Command::Check);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().cloned().ok_or_else(|| format!("`{}` needs a value", arg));
+            match arg.as_str() {
+                "--class" => parsed.classes.push(value()?),
+                "--classpath" => parsed.classpath = Some(value()?),
+                "--out" if generates => parsed.out = Some(value()?),
+                "--jvm-option" if generates => parsed.jvm_options.push(value()?),
+                "--rust-char" => parsed.rust_char = true,
+                "-h" | "--help" => parsed.command = Command::Help,
+                _ => return Err(format!("unexpected argument `{}`", arg)),
+            }
+        }
+        if parsed.command == Command::Help {
+            return Ok(parsed);
+        }
+        if parsed.classes.is_empty() {
+            return Err("expected at least one `--class`".to_string());
+        }
+        if parsed.command == Command::Check && parsed.out.is_none() {
+            return Err("`check` needs the bindings file as `--out`".to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Run the command line in `std::env::args`.
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match Args::parse(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> io::Result<()> {
+    let classes: Vec<&str> = args.classes.iter().map(String::as_str).collect();
+    let mut stdout = io::stdout().lock();
+    match args.command {
+        Command::Help => write!(stdout, "{}", USAGE),
+        Command::Generate | Command::Check => {
+            let jvm_options = if args.jvm_options.is_empty() {
+                args.classpath.as_ref().map(|cp| vec![format!("-Djava.class.path={}", cp)])
+            } else {
+                Some(args.jvm_options.clone())
+            };
+            let options = BindingOptions { rust_char: args.rust_char };
+            let class_path = args.classpath.clone();
+            match (&args.out, args.command) {
+                (Some(out), Command::Check) => {
+                    verify_bindings_file(classes, class_path, Path::new(out), jvm_options, &options)
+                }
+                (Some(out), _) => {
+                    write_bindings_file(classes, class_path, Path::new(out), jvm_options, &options).map(drop)
+                }
+                (None, _) => {
+                    let bindings = generate_bindings(classes, class_path, jvm_options, &options)?;
+                    stdout.write_all(bindings.as_bytes())
+                }
+            }
+        }
+        Command::List => {
+            for class in &classes {
+                let java_class = parse_javap_output(class, args.classpath.clone())?;
+                for line in describe_class(class, java_class) {
+                    writeln!(stdout, "{}", line)?;
+                }
+            }
+            Ok(())
+        }
+        Command::Inspect => {
+            let parsed = classes.iter()
+                .map(|class| Ok(class_json(class, &parse_javap_output(class, args.classpath.clone())?)))
+                .collect::<io::Result<Vec<String>>>()?;
+            writeln!(stdout, "[{}]", parsed.join(","))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON for `inspect`
+// ---------------------------------------------------------------------------
+
+fn class_json(class: &str, java_class: &JavaClass) -> String {
+    let mut json = format!("{{\"class\":{}", string(class));
+    let methods: Vec<String> = java_class.methods.iter().map(method_json).collect();
+    let _ = write!(json, ",\"methods\":[{}]", methods.join(","));
+    match &java_class.record {
+        Some(components) => {
+            let components: Vec<String> = components.iter()
+                .map(|c| format!("{{\"name\":{},\"descriptor\":{}}}", string(&c.name), string(&c.descriptor)))
+                .collect();
+            let _ = write!(json, ",\"record\":[{}]", components.join(","));
+        }
+        None => json.push_str(",\"record\":null"),
+    }
+    let _ = write!(json, ",\"permitted_subclasses\":{}", strings(&java_class.permitted_subclasses));
+    match &java_class.kotlin {
+        Some(kotlin) => {
+            let _ = write!(
+                json,
+                ",\"kotlin\":{{\"kind\":{},\"strings\":{},\"properties\":{}}}",
+                kotlin.kind,
+                strings(&kotlin.strings),
+                strings(&kotlin.properties)
+            );
+        }
+        None => json.push_str(",\"kotlin\":null"),
+    }
+    match &java_class.companion {
+        Some(companion) => {
+            let class = format!("{}${}", class, companion.field);
+            let _ = write!(
+                json,
+                ",\"companion\":{{\"field\":{},\"class\":{}}}",
+                string(&companion.field),
+                class_json(&class, &companion.class)
+            );
+        }
+        None => json.push_str(",\"companion\":null"),
+    }
+    json.push('}');
+    json
+}
+
+fn method_json(binding: &MethodBinding) -> String {
+    format!(
+        "{{\"path\":{},\"name\":{},\"signature\":{},\"args\":{},\"return_type\":{},\"generic_return\":{},\
+         \"is_static\":{},\"is_constructor\":{},\"is_varargs\":{},\"is_synthetic\":{},\"is_native\":{}}}",
+        string(&binding.path),
+        string(&binding.name),
+        string(&binding.signature),
+        strings(&binding.args),
+        string(&binding.return_type),
+        string(&binding.generic_return),
+        binding.is_static,
+        binding.is_constructor,
+        binding.is_varargs,
+        binding.is_synthetic,
+        binding.is_native,
+    )
+}
+
+fn strings(values: &[String]) -> String {
+    let values: Vec<String> = values.iter().map(|v| string(v)).collect();
+    format!("[{}]", values.join(","))
+}
+
+fn string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse(&args.split_whitespace().map(String::from).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse("generate --class com.example.Car --class com.example.Engine --classpath java --out b.rs --rust-char").unwrap();
+        assert_eq!(args.command, Command::Generate);
+        assert_eq!(args.classes, ["com.example.Car", "com.example.Engine"]);
+        assert_eq!(args.classpath.as_deref(), Some("java"));
+        assert_eq!(args.out.as_deref(), Some("b.rs"));
+        assert!(args.rust_char);
+
+        assert_eq!(parse("inspect --class com.example.Car").unwrap().command, Command::Inspect);
+        assert_eq!(parse("check --class com.example.Car --out b.rs").unwrap().command, Command::Check);
+        assert!(parse("check --class com.example.Car").unwrap_err().contains("`--out`"));
+        assert_eq!(parse("list --help").unwrap().command, Command::Help);
+        assert!(parse("list --class com.example.Car --out b.rs").unwrap_err().contains("`--out`"));
+        assert!(parse("generate --class").unwrap_err().contains("needs a value"));
+        assert!(parse("generate").unwrap_err().contains("--class"));
+        assert!(parse("bind --class com.example.Car").unwrap_err().contains("unknown command"));
+    }
+
+    #[test]
+    fn test_class_json() {
+        let class = JavaClass {
+            methods: vec![MethodBinding {
+                path: "com/example/Car".into(),
+                name: "getMake".into(),
+                signature: "()Ljava/lang/String;".into(),
+                args: vec![],
+                return_type: "Ljava/lang/String;".into(),
+                generic_return: "java.lang.String".into(),
+                is_static: false,
+                is_constructor: false,
+                is_varargs: false,
+                is_synthetic: false,
+                is_native: false,
+            }],
+            permitted_subclasses: vec!["com/example/Car$\"Q\"".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            class_json("com.example.Car", &class),
+            "{\"class\":\"com.example.Car\",\"methods\":[{\"path\":\"com/example/Car\",\"name\":\"getMake\",\
+             \"signature\":\"()Ljava/lang/String;\",\"args\":[],\"return_type\":\"Ljava/lang/String;\",\
+             \"generic_return\":\"java.lang.String\",\"is_static\":false,\"is_constructor\":false,\
+             \"is_varargs\":false,\"is_synthetic\":false,\"is_native\":false}],\"record\":null,\
+             \"permitted_subclasses\":[\"com/example/Car$\\\"Q\\\"\"],\"kotlin\":null,\"companion\":null}"
+        );
+        assert_eq!(string("a\tb\\"), "\"a\\u0009b\\\\\"");
+    }
+}
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use jni::signature::{Primitive, ReturnType};
 
-use crate::parse_javap_output;
+use crate::{iterable_supertype, parse_javap_output};
 
 /// Generate a Rust source file with JNI bindings for the given Java classes.
 ///
-/// Call this from your `build.rs`:
+/// Call this from your `build.rs`. It prints `cargo:rerun-if-changed` for
+/// the class path's directories and jars and each class file javap read,
+/// so the bindings are regenerated when a class changes:
 /// ```no_run
 /// auto_jni::generate_bindings_file(
 ///     vec!["com.example.MyClass"],
@@ -24,47 +26,181 @@ pub fn generate_bindings_file(
     output_path: &Path,
     jvm_options: Option<Vec<String>>,
 ) -> std::io::Result<()> {
-    let mut file = File::create(output_path)?;
+    generate_bindings_file_with(classes, class_path, output_path, jvm_options, &BindingOptions::default())
+}
+
+/// How Java types without a single obvious Rust equivalent are mapped.
+#[derive(Debug, Clone, Default)]
+pub struct BindingOptions {
+    /// Map Java `char` to Rust `char` and `char[]` to `String`/`&str`
+    /// instead of `u16` and `Vec<u16>`/`&[u16]`. Values that don't fit
+    /// (surrogates, characters outside the BMP) become
+    /// `JNIError::InvalidChar` at runtime.
+    pub rust_char: bool,
+}
+
+/// [`generate_bindings_file`] with non-default [`BindingOptions`].
+///
+/// The file is only written when its content changes, so bindings checked
+/// into the source tree keep their modification time.
+pub fn generate_bindings_file_with(
+    classes: Vec<&str>,
+    class_path: Option<String>,
+    output_path: &Path,
+    jvm_options: Option<Vec<String>>,
+    options: &BindingOptions,
+) -> std::io::Result<()> {
+    let sources = write_bindings_file(classes, class_path, output_path, jvm_options, options)?;
+    rerun_if_changed(&sources);
+    Ok(())
+}
+
+/// Write the bindings to `output_path` unless it already holds them, and
+/// return the files they were generated from.
+pub(crate) fn write_bindings_file(
+    classes: Vec<&str>,
+    class_path: Option<String>,
+    output_path: &Path,
+    jvm_options: Option<Vec<String>>,
+    options: &BindingOptions,
+) -> std::io::Result<Vec<PathBuf>> {
+    let (bindings, sources) = generate_bindings_with_sources(classes, class_path, jvm_options, options)?;
+    if !fs::read(output_path).is_ok_and(|existing| existing == bindings.as_bytes()) {
+        fs::write(output_path, bindings)?;
+    }
+    Ok(sources)
+}
+
+/// Have Cargo rerun the build script when a class it bound changes. Cargo
+/// watches every file in a directory.
+fn rerun_if_changed(sources: &[PathBuf]) {
+    for source in sources {
+        println!("cargo:rerun-if-changed={}", source.display());
+    }
+}
+
+/// Check that the bindings at `bindings_path`, generated earlier by
+/// [`generate_bindings_file_with`] with the same arguments, are up to
+/// date with the class files.
+///
+/// For bindings checked into the source tree so crates using them don't
+/// need a JDK: run this in CI (or `auto-jni check`) to catch a class that
+/// changed without the bindings being regenerated. Returns an
+/// `InvalidData` error naming the first line that differs.
+pub fn verify_bindings_file(
+    classes: Vec<&str>,
+    class_path: Option<String>,
+    bindings_path: &Path,
+    jvm_options: Option<Vec<String>>,
+    options: &BindingOptions,
+) -> std::io::Result<()> {
+    let bindings = generate_bindings(classes, class_path, jvm_options, options)?;
+    let existing = fs::read_to_string(bindings_path)?;
+    match first_difference(&existing, &bindings) {
+        None => Ok(()),
+        Some(line) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} is out of date with the class files (first difference at line {}); regenerate it",
+                bindings_path.display(),
+                line
+            ),
+        )),
+    }
+}
+
+/// The 1-based line at which `a` and `b` first differ.
+fn first_difference(a: &str, b: &str) -> Option<usize> {
+    if a == b {
+        return None;
+    }
+    let mut a_lines = a.lines();
+    let mut b_lines = b.lines();
+    let mut line = 1;
+    // Equal line by line but unequal overall: they differ in a final newline.
+    while let (Some(a), Some(b)) = (a_lines.next(), b_lines.next()) {
+        if a != b {
+            break;
+        }
+        line += 1;
+    }
+    Some(line)
+}
+
+/// The bindings [`generate_bindings_file_with`] writes, as a string.
+pub fn generate_bindings(
+    classes: Vec<&str>,
+    class_path: Option<String>,
+    jvm_options: Option<Vec<String>>,
+    options: &BindingOptions,
+) -> std::io::Result<String> {
+    generate_bindings_with_sources(classes, class_path, jvm_options, options).map(|(bindings, _)| bindings)
+}
+
+/// [`generate_bindings`], and the files the bindings depend on: the class
+/// path's directories and jars, so a class added earlier on the class path
+/// is noticed too, then the class files (or jars) javap read, for callers
+/// that can't watch a directory.
+#[doc(hidden)]
+pub fn generate_bindings_with_sources(
+    classes: Vec<&str>,
+    class_path: Option<String>,
+    jvm_options: Option<Vec<String>>,
+    options: &BindingOptions,
+) -> std::io::Result<(String, Vec<PathBuf>)> {
+    let mut file = Vec::new();
+    let mut sources: Vec<PathBuf> = class_path.as_deref()
+        .map(|cp| std::env::split_paths(cp).filter(|entry| entry.exists()).collect())
+        .unwrap_or_default();
 
     write_header(&mut file, jvm_options)?;
 
-    for class in classes {
-        let bindings = parse_javap_output(class, class_path.clone());
-        write_class(&mut file, class, bindings)?;
+    let mut with_natives = Vec::new();
+    for class in &classes {
+        let java_class = parse_javap_output(class, class_path.clone())?;
+        let iterable = iterable_supertype(class, class_path.as_deref());
+        let companion_source = java_class.companion.as_ref().and_then(|c| c.class.source.clone());
+        for source in java_class.source.iter().chain(&companion_source) {
+            if !sources.contains(source) {
+                sources.push(source.clone());
+            }
+        }
+        if java_class.methods.iter().any(|b| b.is_native) {
+            with_natives.push(*class);
+        }
+        write_class(&mut file, class, java_class, iterable.as_deref(), &classes, options)?;
+    }
+    if !with_natives.is_empty() {
+        write_on_load(&mut file, &with_natives)?;
     }
 
-    Ok(())
+    // Everything written above is UTF-8.
+    Ok((String::from_utf8(file).unwrap(), sources))
 }
 
 // ---------------------------------------------------------------------------
 // Header (JVM bootstrap + imports)
 // ---------------------------------------------------------------------------
 
-fn write_header(file: &mut File, jvm_options: Option<Vec<String>>) -> std::io::Result<()> {
-    writeln!(file, "use auto_jni::jni::objects::{{JObject, GlobalRef}};")?;
+fn write_header(file: &mut dyn Write, jvm_options: Option<Vec<String>>) -> std::io::Result<()> {
+    writeln!(file, "use auto_jni::jni::objects::{{AutoLocal, JObject, GlobalRef}};")?;
     writeln!(file, "use auto_jni::jni::objects::{{JValue, JObjectArray}};")?;
     writeln!(file, "use auto_jni::jni::signature::{{Primitive, ReturnType}};")?;
-    writeln!(file, "use auto_jni::jni::{{InitArgsBuilder, JNIEnv, JNIVersion, JavaVM}};")?;
-    writeln!(file, "use auto_jni::lazy_static::lazy_static;")?;
+    writeln!(file, "use auto_jni::jni::JNIEnv;")?;
     writeln!(file, "use auto_jni::errors::JNIError;")?;
-    writeln!(file, "use auto_jni::{{call, call_static, create}};")?;
+    writeln!(file, "use auto_jni::{{call, call_static, create, vm, JavaObject}};")?;
     writeln!(file)?;
-    writeln!(file, "lazy_static! {{ static ref JAVA: JavaVM = create_jvm(); }}")?;
-    writeln!(file)?;
-    writeln!(file, "fn create_jvm() -> JavaVM {{")?;
-    writeln!(file, "    let jvm_args = InitArgsBuilder::new()")?;
-    writeln!(file, "        .version(JNIVersion::V8)")?;
+    writeln!(file, "/// JVM options used if nothing started the JVM before the first call.")?;
+    writeln!(file, "pub const JVM_OPTIONS: &[&str] = &[")?;
     if let Some(opts) = jvm_options {
         for opt in opts {
-            writeln!(file, "        .option(\"{}\")", opt.replace('\\', "\\\\"))?;
+            writeln!(file, "    \"{}\",", opt.replace('\\', "\\\\"))?;
         }
     }
-    writeln!(file, "        .build().unwrap();")?;
-    writeln!(file, "    JavaVM::new(jvm_args).unwrap()")?;
-    writeln!(file, "}}")?;
+    writeln!(file, "];")?;
     writeln!(file)?;
     writeln!(file, "pub fn java() -> JNIEnv<'static> {{")?;
-    writeln!(file, "    JAVA.attach_current_thread_permanently().unwrap()")?;
+    writeln!(file, "    vm::get_or_init(JVM_OPTIONS).unwrap().env()")?;
     writeln!(file, "}}")?;
     writeln!(file)
 }
@@ -73,23 +209,484 @@ fn write_header(file: &mut File, jvm_options: Option<Vec<String>>) -> std::io::R
 // Per-class struct + impl
 // ---------------------------------------------------------------------------
 
+/// Rust name for a class: `com.example.Car$Engine` becomes
+/// `com_example_Car_Engine`.
+fn struct_name(class: &str) -> String {
+    class.replace(['.', '$'], "_")
+}
+
 fn write_class(
-    file: &mut File,
+    file: &mut dyn Write,
     class: &str,
-    bindings: Vec<crate::MethodBinding>,
+    java_class: crate::JavaClass,
+    iterable: Option<&str>,
+    bound_classes: &[&str],
+    options: &BindingOptions,
 ) -> std::io::Result<()> {
-    let struct_name = class.replace('.', "_");
+    let crate::JavaClass { methods: bindings, record, permitted_subclasses, kotlin, companion, .. } = java_class;
+    let struct_name = struct_name(class);
+    let iterator = iterable.map(|iterable| element_type(iterable, "java.lang.Iterable", bound_classes, options));
+    // Native methods are implemented in Rust, not called from it.
+    let (natives, bindings): (Vec<_>, Vec<_>) = bindings.into_iter().partition(|b| b.is_native);
+    let methods = kotlin_methods(bindings, kotlin.as_ref(), companion.as_deref());
+
+    // Kotlin top-level functions have no receiver, so they go in a module.
+    if kotlin.as_ref().is_some_and(|k| k.kind == 2 || k.kind == 4) {
+        writeln!(file, "/// Top-level functions from the Kotlin file facade `{}`.", class)?;
+        writeln!(file, "pub mod {} {{", kotlin_facade_module(class))?;
+        writeln!(file, "    use super::*;")?;
+        let functions = methods.into_iter().filter(|(b, _, _)| b.is_static).collect();
+        write_methods(file, functions, bound_classes, options)?;
+        writeln!(file, "}}")?;
+        writeln!(file)?;
+        if !natives.is_empty() {
+            write_natives(file, class, &struct_name, &natives, bound_classes)?;
+        }
+        return Ok(());
+    }
 
     writeln!(file, "pub struct {} {{", struct_name)?;
     writeln!(file, "    inner: GlobalRef,")?;
     writeln!(file, "}}")?;
     writeln!(file)?;
+    write_object_impls(file, class, &struct_name)?;
     writeln!(file, "impl<'a> {} {{", struct_name)?;
+    writeln!(file, "    pub fn from_global(inner: GlobalRef) -> Self {{")?;
+    writeln!(file, "        Self {{ inner }}")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "    pub fn from_local(obj: &JObject) -> Result<Self, JNIError> {{")?;
+    writeln!(file, "        <Self as JavaObject>::from_local(obj)")?;
+    writeln!(file, "    }}")?;
+    if record.is_some() {
+        writeln!(file, "    /// Copy the record's components into a [`{}_Record`].", struct_name)?;
+        writeln!(file, "    pub fn to_record(&self) -> Result<{}_Record, JNIError> {{", struct_name)?;
+        writeln!(file, "        let mut env = java();")?;
+        writeln!(file, "        let obj = env.new_local_ref(&self.inner)?;")?;
+        writeln!(file, "        auto_jni::FromJava::from_java(&mut env, obj)")?;
+        writeln!(file, "    }}")?;
+    }
+    if !permitted_subclasses.is_empty() {
+        write_downcast(file, &struct_name, &permitted_subclasses, bound_classes)?;
+    }
+    if let Some(companion) = &companion {
+        write_companion_accessor(file, class, &companion.field)?;
+    }
 
-    let mut seen_methods: HashMap<String, u32> = HashMap::new();
+    write_methods(file, methods, bound_classes, options)?;
+
+    // Accessor for the wrapped GlobalRef.
+    writeln!(file, "    pub fn inner(&self) -> &GlobalRef {{")?;
+    writeln!(file, "        &self.inner")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    if let Some(item) = iterator {
+        write_iterable_impls(file, &struct_name, &item)?;
+    }
+    if let Some(components) = record {
+        write_record(file, class, &struct_name, &components, bound_classes, options)?;
+    }
+    if !permitted_subclasses.is_empty() {
+        write_sealed_enum(file, class, &struct_name, &permitted_subclasses, bound_classes)?;
+    }
+    if !natives.is_empty() {
+        write_natives(file, class, &struct_name, &natives, bound_classes)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Listing what would be generated (`auto-jni list`)
+// ---------------------------------------------------------------------------
+
+/// One line per item [`write_class`] generates for `java_class`: the
+/// struct (or module, for Kotlin file facades), each method with the Java
+/// method it calls, and the record, sealed enum and natives items.
+#[cfg(feature = "cli")]
+pub(crate) fn describe_class(class: &str, java_class: crate::JavaClass) -> Vec<String> {
+    let crate::JavaClass { methods: bindings, record, permitted_subclasses, kotlin, companion, .. } = java_class;
+    let struct_name = struct_name(class);
+    let (natives, bindings): (Vec<_>, Vec<_>) = bindings.into_iter().partition(|b| b.is_native);
+    let mut methods = kotlin_methods(bindings, kotlin.as_ref(), companion.as_deref());
+
+    let mut lines = Vec::new();
+    if kotlin.as_ref().is_some_and(|k| k.kind == 2 || k.kind == 4) {
+        methods.retain(|(b, _, _)| b.is_static);
+        lines.push(format!("{}: mod {}", class, kotlin_facade_module(class)));
+    } else {
+        lines.push(format!("{}: struct {}", class, struct_name));
+    }
+
+    let names = method_names(&methods);
+    for ((binding, _, on_companion), name) in methods.iter().zip(names) {
+        let kind = if binding.is_constructor {
+            "constructor"
+        } else if *on_companion {
+            "companion"
+        } else if binding.is_static {
+            "static"
+        } else {
+            "method"
+        };
+        let java_name = if binding.is_constructor { "<init>" } else { &binding.name };
+        lines.push(format!("  {:<11} {} -> {}{}", kind, name, java_name, binding.signature));
+    }
+
+    if record.is_some() {
+        lines.push(format!("  {:<11} {}_Record", "record", struct_name));
+    }
+    if !permitted_subclasses.is_empty() {
+        lines.push(format!("  {:<11} {}_Sealed", "sealed", struct_name));
+    }
+    for binding in &natives {
+        lines.push(format!("  {:<11} {}{}", "native", binding.name, binding.signature));
+    }
+    lines
+}
+
+// ---------------------------------------------------------------------------
+// Native methods implemented in Rust
+// ---------------------------------------------------------------------------
+
+/// A `{struct_name}_Natives` trait with one function per `native` method,
+/// and a `{struct_name}_export_natives!(Type)` macro exporting the
+/// `Java_...` symbols the JVM looks up, forwarding to `Type`'s impl.
+fn write_natives(
+    file: &mut dyn Write,
+    class: &str,
+    struct_name: &str,
+    natives: &[crate::MethodBinding],
+    bound_classes: &[&str],
+) -> std::io::Result<()> {
+    let path = class.replace('.', "/");
+    let names = native_names(natives);
+
+    writeln!(file, "/// Rust implementations of the `native` methods of `{}`.", class)?;
+    writeln!(file, "pub trait {}_Natives {{", struct_name)?;
+    for (binding, name) in natives.iter().zip(&names) {
+        write!(file, "    fn {}<'local>(env: &mut JNIEnv<'local>", name)?;
+        if binding.is_static {
+            write!(file, ", class: auto_jni::jni::objects::JClass<'local>")?;
+        } else {
+            write!(file, ", this: JObject<'local>")?;
+        }
+        for (i, ty) in binding.args.iter().enumerate() {
+            write!(file, ", arg_{}: {}", i, native_type(ty))?;
+        }
+        let ret = if binding.return_type == "V" { "()" } else { native_type(&binding.return_type) };
+        writeln!(file, ") -> Result<{}, JNIError>;", ret)?;
+    }
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    writeln!(file, "/// Export the `native` methods of `{}`, implemented by a type", class)?;
+    writeln!(file, "/// implementing [`{}_Natives`].", struct_name)?;
+    writeln!(file, "#[allow(unused_macros)]")?;
+    writeln!(file, "macro_rules! {}_export_natives {{", struct_name)?;
+    writeln!(file, "    ($ty:ty) => {{")?;
+    for (binding, name) in natives.iter().zip(&names) {
+        // Overloaded natives need the argument descriptor in the symbol.
+        let overloaded = natives.iter().filter(|b| b.name == binding.name).count() > 1;
+        let mut symbol = format!("Java_{}_{}", jni_mangle(&path), jni_mangle(&binding.name));
+        if overloaded {
+            let args = binding.signature.trim_start_matches('(').split(')').next().unwrap_or("");
+            symbol.push_str(&format!("__{}", jni_mangle(args)));
+        }
+
+        writeln!(file, "        #[no_mangle]")?;
+        writeln!(file, "        pub extern \"system\" fn {}<'local>(", symbol)?;
+        writeln!(file, "            mut env: auto_jni::jni::JNIEnv<'local>,")?;
+        if binding.is_static {
+            writeln!(file, "            class: auto_jni::jni::objects::JClass<'local>,")?;
+        } else {
+            writeln!(file, "            this: auto_jni::jni::objects::JObject<'local>,")?;
+        }
+        for (i, ty) in binding.args.iter().enumerate() {
+            writeln!(file, "            arg_{}: {},", i, native_abi_type(ty))?;
+        }
+        let default = match binding.return_type.as_str() {
+            "V" => {
+                writeln!(file, "        ) {{")?;
+                "()"
+            }
+            ret if native_type(ret).starts_with("JObject") => {
+                writeln!(file, "        ) -> auto_jni::jni::sys::jobject {{")?;
+                "std::ptr::null_mut()"
+            }
+            ret => {
+                writeln!(file, "        ) -> {} {{", native_abi_type(ret))?;
+                "Default::default()"
+            }
+        };
+        writeln!(file, "            auto_jni::native::guard(&mut env, {}, |env| {{", default)?;
+        write!(file, "                let result = <$ty as {}_Natives>::{}(env, {}", struct_name, name,
+            if binding.is_static { "class" } else { "this" })?;
+        for (i, ty) in binding.args.iter().enumerate() {
+            if ty == "Z" {
+                write!(file, ", arg_{} != 0", i)?;
+            } else {
+                write!(file, ", arg_{}", i)?;
+            }
+        }
+        writeln!(file, ")?;")?;
+        match binding.return_type.as_str() {
+            "Z" => writeln!(file, "                Ok(result as auto_jni::jni::sys::jboolean)")?,
+            "V" | "I" | "J" | "D" | "F" | "B" | "C" | "S" => writeln!(file, "                Ok(result)")?,
+            _ => writeln!(file, "                Ok(result.into_raw())")?,
+        }
+        writeln!(file, "            }})")?;
+        writeln!(file, "        }}")?;
+    }
+    writeln!(file, "    }};")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    write_native_fns(file, class, struct_name, natives, &names, bound_classes)
+}
+
+/// A `{struct_name}_NativeFns` table of optional Rust functions, one per
+/// `native` method, taking and returning converted values. `register`
+/// binds the ones that are set with `RegisterNatives`, through
+/// trampolines that convert arguments and throw errors and panics.
+fn write_native_fns(
+    file: &mut dyn Write,
+    class: &str,
+    struct_name: &str,
+    natives: &[crate::MethodBinding],
+    names: &[String],
+    bound_classes: &[&str],
+) -> std::io::Result<()> {
+    let ty = |descriptor: &str| {
+        let descriptor = match descriptor.strip_prefix('L') {
+            Some(class) => format!("L{};", class.trim_end_matches(';')),
+            None => descriptor.to_string(),
+        };
+        component_type(&descriptor, bound_classes, &BindingOptions::default())
+    };
+    let is_primitive = |descriptor: &str| descriptor.len() == 1;
+    let fn_type = |binding: &crate::MethodBinding| {
+        let mut args: Vec<String> = binding.args.iter().map(|a| ty(a)).collect();
+        if !binding.is_static {
+            args.insert(0, format!("&{}", struct_name));
+        }
+        let ret = if binding.return_type == "V" { "()".to_string() } else { ty(&binding.return_type) };
+        format!("fn({}) -> Result<{}, JNIError>", args.join(", "), ret)
+    };
+
+    writeln!(file, "/// Rust functions for the `native` methods of `{}`, bound with", class)?;
+    writeln!(file, "/// `RegisterNatives` by [`{}_NativeFns::register`].", struct_name)?;
+    writeln!(file, "#[derive(Default)]")?;
+    writeln!(file, "pub struct {}_NativeFns {{", struct_name)?;
+    for (binding, name) in natives.iter().zip(names) {
+        writeln!(file, "    pub {}: Option<{}>,", name, fn_type(binding))?;
+    }
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    writeln!(file, "impl {}_NativeFns {{", struct_name)?;
+    writeln!(file, "    fn registered() -> &'static auto_jni::once_cell::sync::OnceCell<Self> {{")?;
+    writeln!(file, "        static FNS: auto_jni::once_cell::sync::OnceCell<{}_NativeFns> = auto_jni::once_cell::sync::OnceCell::new();", struct_name)?;
+    writeln!(file, "        &FNS")?;
+    writeln!(file, "    }}")?;
+    writeln!(file)?;
+    writeln!(file, "    /// Register the functions that are set, returning the methods that aren't.")?;
+    writeln!(file, "    pub fn register(self, env: &mut JNIEnv<'static>) -> Result<Vec<String>, JNIError> {{")?;
+    for (binding, name) in natives.iter().zip(names) {
+        writeln!(file, "        extern \"system\" fn {}(", name)?;
+        writeln!(file, "            mut env: JNIEnv<'static>,")?;
+        if binding.is_static {
+            writeln!(file, "            _class: auto_jni::jni::objects::JClass<'static>,")?;
+        } else {
+            writeln!(file, "            this: JObject<'static>,")?;
+        }
+        for (i, arg) in binding.args.iter().enumerate() {
+            let abi = if is_primitive(arg) { native_abi_type(arg) } else { "JObject<'static>" };
+            writeln!(file, "            arg_{}: {},", i, abi)?;
+        }
+        let (abi_ret, default) = match binding.return_type.as_str() {
+            "V" => ("()", "()"),
+            r if is_primitive(r) => (native_abi_type(r), "Default::default()"),
+            _ => ("auto_jni::jni::sys::jobject", "std::ptr::null_mut()"),
+        };
+        writeln!(file, "        ) -> {} {{", abi_ret)?;
+        let converts = !binding.is_static
+            || !is_primitive(&binding.return_type)
+            || binding.args.iter().any(|a| !is_primitive(a));
+        writeln!(file, "            auto_jni::native::guard(&mut env, {}, |{}| {{", default,
+            if converts { "env" } else { "_" })?;
+        writeln!(file, "                let f = {}_NativeFns::registered().get().and_then(|fns| fns.{}).unwrap();", struct_name, name)?;
+        let mut call_args = Vec::new();
+        if !binding.is_static {
+            writeln!(file, "                let this = <{} as auto_jni::FromJava>::from_java(env, this)?;", struct_name)?;
+            call_args.push("&this".to_string());
+        }
+        for (i, arg) in binding.args.iter().enumerate() {
+            match arg.as_str() {
+                "Z" => call_args.push(format!("arg_{} != 0", i)),
+                a if is_primitive(a) => call_args.push(format!("arg_{}", i)),
+                a => {
+                    writeln!(file, "                let arg_{} = <{} as auto_jni::FromJava>::from_java(env, arg_{})?;", i, ty(a), i)?;
+                    call_args.push(format!("arg_{}", i));
+                }
+            }
+        }
+        writeln!(file, "                let result = f({})?;", call_args.join(", "))?;
+        match binding.return_type.as_str() {
+            "V" => writeln!(file, "                Ok(result)")?,
+            "Z" => writeln!(file, "                Ok(result as auto_jni::jni::sys::jboolean)")?,
+            r if is_primitive(r) => writeln!(file, "                Ok(result)")?,
+            r => {
+                // Wrappers and `GlobalRef`s convert by reference.
+                let rust = ty(r);
+                let by_ref = rust == "GlobalRef" || bound_classes.iter().any(|c| self::struct_name(c) == rust);
+                writeln!(file, "                Ok(auto_jni::IntoJava::into_java({}result, env)?.into_raw())",
+                    if by_ref { "&" } else { "" })?;
+            }
+        }
+        writeln!(file, "            }})")?;
+        writeln!(file, "        }}")?;
+    }
+    writeln!(file)?;
+    writeln!(file, "        let mut methods = Vec::new();")?;
+    writeln!(file, "        let mut missing = Vec::new();")?;
+    for (binding, name) in natives.iter().zip(names) {
+        writeln!(file, "        if self.{}.is_some() {{", name)?;
+        writeln!(file, "            methods.push(auto_jni::native::method(\"{}\", \"{}\", {} as *mut std::ffi::c_void));",
+            binding.name, binding.signature, name)?;
+        writeln!(file, "        }} else {{")?;
+        writeln!(file, "            missing.push(\"{}.{}{}\".to_string());", class, binding.name, binding.signature)?;
+        writeln!(file, "        }}")?;
+    }
+    writeln!(file, "        Self::registered().set(self).map_err(|_| JNIError::AlreadyInitialized)?;")?;
+    writeln!(file, "        if !methods.is_empty() {{")?;
+    writeln!(file, "            env.register_native_methods(\"{}\", &methods)?;", class.replace('.', "/"))?;
+    writeln!(file, "        }}")?;
+    writeln!(file, "        Ok(missing)")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)
+}
+
+/// A `NativeFns` struct holding the function table of every bound class
+/// with `native` methods, and a `jni_on_load!(fns)` macro defining a
+/// `JNI_OnLoad` that registers them when the library is loaded.
+fn write_on_load(file: &mut dyn Write, classes: &[&str]) -> std::io::Result<()> {
+    writeln!(file, "/// Rust functions for the `native` methods of every bound class.")?;
+    writeln!(file, "#[derive(Default)]")?;
+    writeln!(file, "pub struct NativeFns {{")?;
+    for class in classes {
+        writeln!(file, "    pub {0}: {0}_NativeFns,", struct_name(class))?;
+    }
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl NativeFns {{")?;
+    writeln!(file, "    /// Register every class's functions, returning the methods without one.")?;
+    writeln!(file, "    pub fn register(self, env: &mut JNIEnv<'static>) -> Result<Vec<String>, JNIError> {{")?;
+    writeln!(file, "        let mut missing = Vec::new();")?;
+    for class in classes {
+        writeln!(file, "        missing.extend(self.{}.register(env)?);", struct_name(class))?;
+    }
+    writeln!(file, "        Ok(missing)")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "/// Define `JNI_OnLoad`, registering a [`NativeFns`] when the library is")?;
+    writeln!(file, "/// loaded. Loading fails with an `UnsatisfiedLinkError` listing any")?;
+    writeln!(file, "/// `native` method left without a function.")?;
+    writeln!(file, "#[allow(unused_macros)]")?;
+    writeln!(file, "macro_rules! jni_on_load {{")?;
+    writeln!(file, "    ($fns:expr) => {{")?;
+    writeln!(file, "        #[no_mangle]")?;
+    writeln!(file, "        pub extern \"system\" fn JNI_OnLoad(")?;
+    writeln!(file, "            vm: *mut auto_jni::jni::sys::JavaVM,")?;
+    writeln!(file, "            _reserved: *mut std::ffi::c_void,")?;
+    writeln!(file, "        ) -> auto_jni::jni::sys::jint {{")?;
+    writeln!(file, "            unsafe {{ auto_jni::native::on_load(vm, |env| NativeFns::register($fns, env)) }}")?;
+    writeln!(file, "        }}")?;
+    writeln!(file, "    }};")?;
+    writeln!(file, "}}")?;
+    writeln!(file)
+}
+
+/// Trait function names, with overloads numbered like bound methods.
+fn native_names(natives: &[crate::MethodBinding]) -> Vec<String> {
+    let mut seen: HashMap<&str, u32> = HashMap::new();
+    natives.iter()
+        .map(|binding| {
+            let count = seen.entry(&binding.name).or_insert(0);
+            let name = if *count == 0 {
+                binding.name.clone()
+            } else {
+                format!("{}_{}", binding.name, count)
+            };
+            *count += 1;
+            name
+        })
+        .collect()
+}
+
+/// Rust type of a native method parameter or result in the trait.
+fn native_type(ty: &str) -> &'static str {
+    match ty {
+        "I" => "i32",
+        "J" => "i64",
+        "D" => "f64",
+        "F" => "f32",
+        "Z" => "bool",
+        "B" => "i8",
+        "C" => "u16",
+        "S" => "i16",
+        _ => "JObject<'local>",
+    }
+}
+
+/// The same type in the exported `extern "system"` function.
+fn native_abi_type(ty: &str) -> &'static str {
+    match ty {
+        "Z" => "auto_jni::jni::sys::jboolean",
+        "I" | "J" | "D" | "F" | "B" | "C" | "S" => native_type(ty),
+        _ => "auto_jni::jni::objects::JObject<'local>",
+    }
+}
+
+/// Escape a class, method or descriptor for a `Java_...` symbol, as the
+/// JNI specification describes: `/` separates, `_`, `;` and `[` become
+/// `_1`, `_2` and `_3`, and anything else that isn't ASCII alphanumeric
+/// (including `$`) becomes `_0xxxx` for each UTF-16 code unit.
+fn jni_mangle(name: &str) -> String {
+    let mut mangled = String::new();
+    for c in name.chars() {
+        match c {
+            '/' => mangled.push('_'),
+            '_' => mangled.push_str("_1"),
+            ';' => mangled.push_str("_2"),
+            '[' => mangled.push_str("_3"),
+            c if c.is_ascii_alphanumeric() => mangled.push(c),
+            c => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    mangled.push_str(&format!("_0{:04x}", unit));
+                }
+            }
+        }
+    }
+    mangled
+}
+
+/// A method to generate: the binding, its Rust name if it isn't the Java
+/// one, and whether it is called on the Kotlin companion object.
+type MethodItem = (crate::MethodBinding, Option<String>, bool);
+
+fn write_methods(
+    file: &mut dyn Write,
+    methods: Vec<MethodItem>,
+    bound_classes: &[&str],
+    options: &BindingOptions,
+) -> std::io::Result<()> {
     let mut seen_enum_helpers: Vec<String> = Vec::new();
+    let names = method_names(&methods);
 
-    for mut binding in bindings {
+    for ((binding, _, on_companion), method_name) in methods.into_iter().zip(names) {
         // Generate a valueOf helper for each unique inner-class/enum arg type.
         for arg in &binding.args {
             if arg.contains('$') {
@@ -102,36 +699,452 @@ fn write_class(
             }
         }
 
-        // Strip lambda synthetic names (e.g. "lambda$foo$1" → "foo")
-        if binding.name.contains('$') {
-            let mut parts = binding.name.splitn(3, '$');
-            parts.next(); // "lambda"
-            binding.name = parts.next().unwrap_or("unknown").to_string();
+        write_method(file, &binding, &method_name, on_companion, bound_classes, options)?;
+    }
+    Ok(())
+}
+
+/// The Rust name of each method: `new` for constructors, otherwise its
+/// Rust name or Java name, with overloads disambiguated by a counter
+/// suffix.
+fn method_names(methods: &[MethodItem]) -> Vec<String> {
+    let mut seen_methods: HashMap<String, u32> = HashMap::new();
+    methods.iter()
+        .map(|(binding, rust_name, _)| {
+            let base_name = if binding.is_constructor {
+                "new".to_string()
+            } else {
+                rust_name.clone().unwrap_or_else(|| strip_lambda(&binding.name).to_string())
+            };
+
+            // Disambiguate overloads by appending a counter suffix.
+            let count = seen_methods.entry(base_name.clone()).or_insert(0);
+            let method_name = if *count == 0 {
+                base_name
+            } else {
+                format!("{}_{}", base_name, count)
+            };
+            *count += 1;
+            rust_ident(&method_name)
+        })
+        .collect()
+}
+
+/// Name lambda bodies after their method ("lambda$foo$1" → "foo"). Only
+/// the Rust name changes; calls use the Java one.
+fn strip_lambda(name: &str) -> &str {
+    match name.strip_prefix("lambda$") {
+        Some(rest) => rest.split('$').next().unwrap_or(rest),
+        None => name,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Kotlin: companion objects, file facades, properties
+// ---------------------------------------------------------------------------
+
+/// The methods to generate for a class. Kotlin classes lose their
+/// synthetic methods (`$default` bridges, `access$` accessors), get Rust
+/// names for property accessors, and gain their companion's methods as
+/// static functions, except those already there through `@JvmStatic`.
+fn kotlin_methods(
+    bindings: Vec<crate::MethodBinding>,
+    kotlin: Option<&crate::KotlinMetadata>,
+    companion: Option<&crate::Companion>,
+) -> Vec<MethodItem> {
+    let Some(kotlin) = kotlin else {
+        return bindings.into_iter().map(|b| (b, None, false)).collect();
+    };
+
+    let mut methods: Vec<MethodItem> = bindings.into_iter()
+        .filter(|b| !b.is_synthetic)
+        .map(|b| {
+            let name = kotlin_property_name(&b, kotlin);
+            (b, name, false)
+        })
+        .collect();
+
+    if let Some(companion) = companion {
+        let companion_kotlin = companion.class.kotlin.as_ref().unwrap_or(kotlin);
+        for binding in &companion.class.methods {
+            let is_jvm_static = methods.iter()
+                .any(|(b, _, _)| b.is_static && b.name == binding.name && b.args == binding.args);
+            if binding.is_constructor || binding.is_static || binding.is_synthetic || is_jvm_static {
+                continue;
+            }
+            let name = kotlin_property_name(binding, companion_kotlin);
+            methods.push((binding.clone(), name, true));
         }
+    }
+    methods
+}
+
+/// `balance` for `getBalance()` and `set_balance` for `setBalance(int)`
+/// when the metadata names a `balance` property. `isOpen()` keeps its name.
+fn kotlin_property_name(binding: &crate::MethodBinding, kotlin: &crate::KotlinMetadata) -> Option<String> {
+    let is_property = |name: &str| kotlin.properties.iter().any(|p| p == name);
+    let decapitalize = |name: &str| {
+        let mut chars = name.chars();
+        chars.next().map_or(String::new(), |c| c.to_lowercase().chain(chars).collect())
+    };
+
+    if binding.is_constructor || binding.is_static {
+        return None;
+    }
+    if binding.args.is_empty() && binding.return_type != "V" {
+        let property = decapitalize(binding.name.strip_prefix("get")?);
+        return is_property(&property).then_some(property);
+    }
+    if binding.args.len() == 1 && binding.return_type == "V" {
+        let rest = binding.name.strip_prefix("set")?;
+        let property = [decapitalize(rest), format!("is{}", rest)].into_iter().find(|p| is_property(p))?;
+        return Some(format!("set_{}", property));
+    }
+    None
+}
+
+/// Module name for a file facade: `com.example.StringUtilsKt` becomes
+/// `com_example_string_utils`.
+fn kotlin_facade_module(class: &str) -> String {
+    let (package, name) = class.rsplit_once('.').unwrap_or(("", class));
+    let name = name.strip_suffix("Kt").unwrap_or(name);
 
-        let base_name = if binding.is_constructor {
-            "new".to_string()
+    let mut module = package.replace('.', "_");
+    if !module.is_empty() {
+        module.push('_');
+    }
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                module.push('_');
+            }
+            module.extend(c.to_lowercase());
         } else {
-            binding.name.clone()
-        };
+            module.push(c);
+        }
+    }
+    module
+}
+
+fn write_companion_accessor(file: &mut dyn Write, class: &str, field: &str) -> std::io::Result<()> {
+    let path = class.replace('.', "/");
+    writeln!(file, "    /// The Kotlin companion object, `{}.{}`.", class, field)?;
+    writeln!(file, "    pub fn companion() -> &'static GlobalRef {{")?;
+    writeln!(file, "        static COMPANION: auto_jni::once_cell::sync::OnceCell<GlobalRef> = auto_jni::once_cell::sync::OnceCell::new();")?;
+    writeln!(file, "        COMPANION.get_or_init(|| {{")?;
+    writeln!(file, "            let mut env = java();")?;
+    writeln!(file, "            let companion = env.get_static_field(\"{}\", \"{}\", \"L{}${};\").unwrap().l().unwrap();", path, field, path, field)?;
+    writeln!(file, "            let companion = env.auto_local(companion);")?;
+    writeln!(file, "            env.new_global_ref(&companion).unwrap()")?;
+    writeln!(file, "        }})")?;
+    writeln!(file, "    }}")
+}
+
+// ---------------------------------------------------------------------------
+// Sealed hierarchies as Rust enums
+// ---------------------------------------------------------------------------
+
+/// Variant name and payload type for each permitted subclass. Subclasses
+/// that aren't bound are carried as a `GlobalRef`.
+fn sealed_variants(permitted_subclasses: &[String], bound_classes: &[&str]) -> Vec<(String, String)> {
+    permitted_subclasses.iter()
+        .map(|path| {
+            let class = path.replace('/', ".");
+            let variant = class.rsplit(['.', '$']).next().unwrap_or(&class).to_string();
+            let payload = if bound_classes.contains(&class.as_str()) {
+                struct_name(&class)
+            } else {
+                "GlobalRef".to_string()
+            };
+            (variant, payload)
+        })
+        .collect()
+}
+
+fn write_sealed_enum(
+    file: &mut dyn Write,
+    class: &str,
+    struct_name: &str,
+    permitted_subclasses: &[String],
+    bound_classes: &[&str],
+) -> std::io::Result<()> {
+    writeln!(file, "/// The permitted subclasses of the sealed `{}`.", class)?;
+    writeln!(file, "#[derive(Debug, Clone)]")?;
+    writeln!(file, "pub enum {}_Sealed {{", struct_name)?;
+    for (variant, payload) in sealed_variants(permitted_subclasses, bound_classes) {
+        writeln!(file, "    {}({}),", variant, payload)?;
+    }
+    writeln!(file, "}}")?;
+    writeln!(file)
+}
 
-        // Disambiguate overloads by appending a counter suffix.
-        let count = seen_methods.entry(base_name.clone()).or_insert(0);
-        let method_name = if *count == 0 {
-            base_name.clone()
+/// `downcast()`, checking the runtime class against each permitted
+/// subclass in declaration order.
+fn write_downcast(
+    file: &mut dyn Write,
+    struct_name: &str,
+    permitted_subclasses: &[String],
+    bound_classes: &[&str],
+) -> std::io::Result<()> {
+    let variants = sealed_variants(permitted_subclasses, bound_classes);
+    writeln!(file, "    /// The permitted subclass this object is an instance of.")?;
+    writeln!(file, "    pub fn downcast(&self) -> Result<{}_Sealed, JNIError> {{", struct_name)?;
+    writeln!(file, "        let mut env = java();")?;
+    for (path, (variant, payload)) in permitted_subclasses.iter().zip(variants) {
+        let value = if payload == "GlobalRef" {
+            "self.inner.clone()".to_string()
         } else {
-            format!("{}_{}", base_name, count)
+            format!("<{} as JavaObject>::from_global(self.inner.clone())", payload)
+        };
+        writeln!(file, "        if env.is_instance_of(&self.inner, \"{}\")? {{", path)?;
+        writeln!(file, "            return Ok({}_Sealed::{}({}));", struct_name, variant, value)?;
+        writeln!(file, "        }}")?;
+    }
+    writeln!(file, "        Err(JNIError::ClassType)")?;
+    writeln!(file, "    }}")
+}
+
+// ---------------------------------------------------------------------------
+// Plain Rust structs for records
+// ---------------------------------------------------------------------------
+
+/// A `{struct_name}_Record` struct with one public field per component,
+/// converted with `FromJava` (through the accessors) and `IntoJava`
+/// (through the canonical constructor).
+fn write_record(
+    file: &mut dyn Write,
+    class: &str,
+    struct_name: &str,
+    components: &[crate::RecordComponent],
+    bound_classes: &[&str],
+    options: &BindingOptions,
+) -> std::io::Result<()> {
+    let path = class.replace('.', "/");
+    let record_name = format!("{}_Record", struct_name);
+    // (Java name, Rust field name, descriptor, Rust type)
+    let fields: Vec<(&str, String, &str, String)> = components.iter()
+        .map(|c| (
+            c.name.as_str(),
+            rust_ident(&c.name),
+            c.descriptor.as_str(),
+            component_type(&c.descriptor, bound_classes, options),
+        ))
+        .collect();
+
+    // `GlobalRef` has no `PartialEq`.
+    if fields.iter().any(|(_, _, _, ty)| ty == "GlobalRef") {
+        writeln!(file, "#[derive(Debug, Clone)]")?;
+    } else {
+        writeln!(file, "#[derive(Debug, Clone, PartialEq)]")?;
+    }
+    writeln!(file, "pub struct {} {{", record_name)?;
+    for (_, field, _, ty) in &fields {
+        writeln!(file, "    pub {}: {},", field, ty)?;
+    }
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    writeln!(file, "impl {} {{", record_name)?;
+    writeln!(file, "    /// A new `{}` with these components.", class)?;
+    writeln!(file, "    pub fn to_java(&self) -> Result<{}, JNIError> {{", struct_name)?;
+    writeln!(file, "        let mut env = java();")?;
+    writeln!(file, "        let obj = auto_jni::IntoJava::into_java(self, &mut env)?;")?;
+    writeln!(file, "        <{} as JavaObject>::from_local(&env.auto_local(obj))", struct_name)?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    writeln!(file, "impl auto_jni::FromJava for {} {{", record_name)?;
+    // `__env` and `__obj` can't be shadowed by the components' locals.
+    writeln!(file, "    fn from_java(__env: &mut JNIEnv<'static>, __obj: JObject<'static>) -> Result<Self, JNIError> {{")?;
+    writeln!(file, "        if __obj.is_null() {{")?;
+    writeln!(file, "            return Err(JNIError::NullPtr);")?;
+    writeln!(file, "        }}")?;
+    for (name, field, descriptor, ty) in &fields {
+        let ret = get_return_type(descriptor);
+        writeln!(file, "        let {} = call!(", field)?;
+        writeln!(file, "            env = __env,")?;
+        writeln!(file, "            &__obj,")?;
+        writeln!(file, "            \"{}\",", path)?;
+        writeln!(file, "            \"{}\",", name)?;
+        writeln!(file, "            \"(){}\",", descriptor)?;
+        writeln!(file, "            &[],")?;
+        writeln!(file, "            {}", return_type_to_string(ret.clone()))?;
+        writeln!(file, "        );")?;
+        let value = match ret {
+            ReturnType::Object => format!("<{} as auto_jni::FromJava>::from_java(__env, {}.l()?)?", ty, field),
+            _ if *descriptor == "C" && options.rust_char => format!("auto_jni::chars::to_char({}.c()?)?", field),
+            _ => format!("{}.{}()?", field, descriptor.to_lowercase()),
         };
-        *count += 1;
+        writeln!(file, "        let {} = {};", field, value)?;
+    }
+    writeln!(file, "        __env.delete_local_ref(__obj)?;")?;
+    write!(file, "        Ok(Self {{ ")?;
+    for (i, (_, field, _, _)) in fields.iter().enumerate() {
+        if i > 0 { write!(file, ", ")?; }
+        write!(file, "{}", field)?;
+    }
+    writeln!(file, " }})")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
 
-        write_method(file, &binding, &method_name)?;
+    let signature: String = components.iter().map(|c| c.descriptor.as_str()).collect();
+    writeln!(file, "impl auto_jni::IntoJava for &{} {{", record_name)?;
+    writeln!(file, "    fn into_java(self, __env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {{")?;
+    for (_, field, descriptor, ty) in &fields {
+        match get_return_type(descriptor) {
+            ReturnType::Object => {
+                let value = match ty.as_str() {
+                    "String" => format!("self.{}.as_str()", field),
+                    "i32" | "i64" | "f64" | "f32" | "bool" | "i8" | "i16" | "u16" | "char" => format!("self.{}", field),
+                    _ => format!("&self.{}", field),
+                };
+                writeln!(file, "        let {} = auto_jni::IntoJava::into_java({}, __env)?;", field, value)?;
+                writeln!(file, "        let {} = __env.auto_local({});", field, field)?;
+            }
+            _ if *descriptor == "C" && options.rust_char => {
+                writeln!(file, "        let {} = auto_jni::chars::to_java_char(self.{})?;", field, field)?;
+            }
+            _ => writeln!(file, "        let {} = self.{};", field, field)?,
+        }
+    }
+    writeln!(file, "        let __obj = create!(env = __env, \"{}\", \"({})V\", &[", path, signature)?;
+    for (_, field, descriptor, _) in &fields {
+        match get_return_type(descriptor) {
+            ReturnType::Object => writeln!(file, "            JValue::Object(&{}).as_jni(),", field)?,
+            _ => writeln!(file, "            {},", jvalue_for(field, descriptor, options))?,
+        }
     }
+    writeln!(file, "        ]);")?;
+    writeln!(file, "        Ok(__env.new_local_ref(&__obj)?)")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)
+}
 
-    // Accessor for the wrapped GlobalRef.
-    writeln!(file, "    pub fn inner(&self) -> &GlobalRef {{")?;
+/// `name` as a Rust identifier: keywords become raw identifiers
+/// (`r#type`), and those that can't be raw get a trailing underscore.
+/// `$`, legal in JVM names, becomes `_`.
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+        "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+        "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+        "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+        "where", "while", "yield",
+    ];
+    match name {
+        "self" | "Self" | "super" | "crate" | "_" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.replace('$', "_"),
+    }
+}
+
+/// Rust field type for a record component's descriptor.
+fn component_type(descriptor: &str, bound_classes: &[&str], options: &BindingOptions) -> String {
+    let class = descriptor.strip_prefix('L').and_then(|d| d.strip_suffix(';')).unwrap_or("").replace('/', ".");
+    match descriptor {
+        "C" if options.rust_char => "char".to_string(),
+        "I" | "J" | "D" | "F" | "Z" | "B" | "C" | "S" => return_type_to_rust_str(get_return_type(descriptor)).to_string(),
+        _ if bound_classes.contains(&class.as_str()) => struct_name(&class),
+        _ => match element_type(&format!("T<{}>", class), "T", &[], options).as_str() {
+            "AutoLocal<'static, JObject<'static>>" => "GlobalRef".to_string(),
+            ty => ty.to_string(),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IntoIterator for classes implementing `java.lang.Iterable`
+// ---------------------------------------------------------------------------
+
+fn write_iterable_impls(file: &mut dyn Write, struct_name: &str, item: &str) -> std::io::Result<()> {
+    for target in [format!("&{}", struct_name), struct_name.to_string()] {
+        writeln!(file, "impl IntoIterator for {} {{", target)?;
+        writeln!(file, "    type Item = {};", item)?;
+        writeln!(file, "    type IntoIter = auto_jni::iter::JIterator<{}>;", item)?;
+        writeln!(file, "    fn into_iter(self) -> Self::IntoIter {{")?;
+        writeln!(file, "        auto_jni::iter::JIterator::from_iterable(self.inner.as_obj())")?;
+        writeln!(file, "    }}")?;
+        writeln!(file, "}}")?;
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+/// Rust element type for a single-parameter generic like
+/// `java.util.Iterator<T>`, falling back to an untyped local reference when
+/// `T` is unknown or not something we can convert.
+fn element_type(generic: &str, container: &str, bound_classes: &[&str], options: &BindingOptions) -> String {
+    let element = generic
+        .strip_prefix(container)
+        .and_then(|rest| rest.strip_prefix('<'))
+        .and_then(|rest| rest.strip_suffix('>'))
+        .unwrap_or("");
+
+    match element {
+        "java.lang.String" => "String".to_string(),
+        "java.lang.Integer" => "i32".to_string(),
+        "java.lang.Long" => "i64".to_string(),
+        "java.lang.Double" => "f64".to_string(),
+        "java.lang.Float" => "f32".to_string(),
+        "java.lang.Boolean" => "bool".to_string(),
+        "java.lang.Byte" => "i8".to_string(),
+        "java.lang.Short" => "i16".to_string(),
+        "java.lang.Character" if options.rust_char => "char".to_string(),
+        "java.lang.Character" => "u16".to_string(),
+        t if bound_classes.contains(&t) => struct_name(t),
+        _ => "AutoLocal<'static, JObject<'static>>".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JavaObject, Clone, Debug and the java.lang.Object protocol
+// ---------------------------------------------------------------------------
+
+fn write_object_impls(file: &mut dyn Write, class: &str, struct_name: &str) -> std::io::Result<()> {
+    writeln!(file, "impl JavaObject for {} {{", struct_name)?;
+    writeln!(file, "    const CLASS: &'static str = \"{}\";", class.replace('.', "/"))?;
+    writeln!(file, "    fn from_global(inner: GlobalRef) -> Self {{")?;
+    writeln!(file, "        Self {{ inner }}")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "    fn inner(&self) -> &GlobalRef {{")?;
     writeln!(file, "        &self.inner")?;
     writeln!(file, "    }}")?;
     writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl Clone for {} {{", struct_name)?;
+    writeln!(file, "    fn clone(&self) -> Self {{")?;
+    writeln!(file, "        Self {{ inner: java().new_global_ref(&self.inner).unwrap() }}")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl std::fmt::Debug for {} {{", struct_name)?;
+    writeln!(file, "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{")?;
+    writeln!(file, "        write!(f, \"{}@{{:x}}\", auto_jni::object::identity_hash_code(self.inner.as_obj()))", class)?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl std::fmt::Display for {} {{", struct_name)?;
+    writeln!(file, "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{")?;
+    writeln!(file, "        f.write_str(&auto_jni::object::to_string(self.inner.as_obj()).map_err(|_| std::fmt::Error)?)")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl PartialEq for {} {{", struct_name)?;
+    writeln!(file, "    fn eq(&self, other: &Self) -> bool {{")?;
+    writeln!(file, "        auto_jni::object::equals(self.inner.as_obj(), other.inner.as_obj())")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    writeln!(file, "impl Eq for {} {{}}", struct_name)?;
+    writeln!(file)?;
+    writeln!(file, "impl std::hash::Hash for {} {{", struct_name)?;
+    writeln!(file, "    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {{")?;
+    writeln!(file, "        std::hash::Hash::hash(&auto_jni::object::hash_code(self.inner.as_obj()), state)")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
     writeln!(file)
 }
 
@@ -139,16 +1152,20 @@ fn write_class(
 // Enum valueOf helper
 // ---------------------------------------------------------------------------
 
-fn write_enum_helper(file: &mut File, enum_path: &str) -> std::io::Result<()> {
+fn write_enum_helper(file: &mut dyn Write, enum_path: &str) -> std::io::Result<()> {
     let fn_name = enum_path.replace('/', "_").replace('$', "_");
-    writeln!(file, "    pub fn {}_from_str(s: &str) -> JObject<'static> {{", fn_name)?;
-    writeln!(file, "        call_static!(")?;
+    writeln!(file, "    pub fn {}_from_str(s: &str) -> AutoLocal<'static, JObject<'static>> {{", fn_name)?;
+    writeln!(file, "        let mut env = java();")?;
+    writeln!(file, "        let s = env.auto_local(env.new_string(s).unwrap());")?;
+    writeln!(file, "        let result = call_static!(")?;
+    writeln!(file, "            env = &mut env,")?;
     writeln!(file, "            \"{}\",", enum_path)?;
     writeln!(file, "            \"valueOf\",")?;
     writeln!(file, "            \"(Ljava/lang/String;)L{};\",", enum_path)?;
-    writeln!(file, "            &[JValue::Object(&java().new_string(s).unwrap()).as_jni()],")?;
+    writeln!(file, "            &[JValue::Object(&s).as_jni()],")?;
     writeln!(file, "            ReturnType::Object")?;
-    writeln!(file, "        ).l().unwrap()")?;
+    writeln!(file, "        );")?;
+    writeln!(file, "        env.auto_local(result.l().unwrap())")?;
     writeln!(file, "    }}")
 }
 
@@ -157,100 +1174,136 @@ fn write_enum_helper(file: &mut File, enum_path: &str) -> std::io::Result<()> {
 // ---------------------------------------------------------------------------
 
 fn write_method(
-    file: &mut File,
+    file: &mut dyn Write,
     binding: &crate::MethodBinding,
     method_name: &str,
+    on_companion: bool,
+    bound_classes: &[&str],
+    options: &BindingOptions,
 ) -> std::io::Result<()> {
-    let args: Vec<(String, String)> = binding.args.iter().enumerate()
+    let mut args: Vec<(String, String)> = binding.args.iter().enumerate()
         .map(|(i, t)| (format!("arg_{}", i), t.clone()))
         .collect();
+    // Varargs take a Rust slice; mark the trailing array as `...X`.
+    if binding.is_varargs {
+        if let Some((_, ty)) = args.last_mut() {
+            if let Some(element) = ty.strip_prefix('[').filter(|e| !e.starts_with('[')) {
+                *ty = format!("...{}", element);
+            }
+        }
+    }
 
     if binding.is_constructor {
-        write_constructor(file, binding, method_name, &args)
+        write_constructor(file, binding, method_name, &args, options)
     } else if binding.is_static {
-        write_static_method(file, binding, method_name, &args)
+        write_static_method(file, binding, method_name, &args, bound_classes, options)
     } else {
-        write_instance_method(file, binding, method_name, &args)
+        write_instance_method(file, binding, method_name, &args, on_companion, bound_classes, options)
     }
 }
 
 fn write_constructor(
-    file: &mut File,
+    file: &mut dyn Write,
     binding: &crate::MethodBinding,
     method_name: &str,
     args: &[(String, String)],
+    options: &BindingOptions,
 ) -> std::io::Result<()> {
     write!(file, "    pub fn {}(", method_name)?;
-    write_arg_params(file, args)?;
+    write_arg_params(file, args, options)?;
     writeln!(file, ") -> Result<Self, JNIError> {{")?;
+    write_arg_conversions(file, args, options)?;
     writeln!(file, "        Ok(Self {{")?;
-    write!(file, "            inner: create!(\"{}\", \"{}\", &[", binding.path, binding.signature)?;
-    write_arg_values(file, args)?;
+    write!(file, "            inner: create!(env = &mut java(), \"{}\", \"{}\", &[", binding.path, binding.signature)?;
+    write_arg_values(file, args, options)?;
     writeln!(file, "])")?;
     writeln!(file, "        }})")?;
+    write_arg_conversions_end(file, args)?;
     writeln!(file, "    }}")
 }
 
 fn write_static_method(
-    file: &mut File,
+    file: &mut dyn Write,
     binding: &crate::MethodBinding,
     method_name: &str,
     args: &[(String, String)],
+    bound_classes: &[&str],
+    options: &BindingOptions,
 ) -> std::io::Result<()> {
     let ret = get_return_type(&binding.return_type);
-    let rust_ret = return_type_to_rust_str(ret.clone());
+    let (rust_ret, result) = rust_return(binding, ret.clone(), bound_classes, options);
 
     write!(file, "    pub fn {}(", method_name)?;
-    write_arg_params(file, args)?;
+    write_arg_params(file, args, options)?;
     writeln!(file, ") -> Result<{}, JNIError> {{", rust_ret)?;
+    write_arg_conversions(file, args, options)?;
 
     if ret == ReturnType::Primitive(Primitive::Void) {
         writeln!(file, "        call_static!(")?;
     } else {
         writeln!(file, "        let result = call_static!(")?;
     }
+    writeln!(file, "            env = &mut java(),")?;
     writeln!(file, "            \"{}\",", binding.path)?;
     writeln!(file, "            \"{}\",", binding.name)?;
     writeln!(file, "            \"{}\",", binding.signature)?;
     write!(file, "            &[")?;
-    write_arg_values(file, args)?;
+    write_arg_values(file, args, options)?;
     writeln!(file, "],")?;
     writeln!(file, "            {}", return_type_to_string(ret.clone()))?;
     writeln!(file, "        );")?;
-    writeln!(file, "        Ok({})", unwrap_result(ret))?;
+    writeln!(file, "        Ok({})", result)?;
+    write_arg_conversions_end(file, args)?;
     writeln!(file, "    }}")
 }
 
+/// An instance method, called on the wrapped object or, hoisted to a
+/// static function, on the Kotlin companion object.
 fn write_instance_method(
-    file: &mut File,
+    file: &mut dyn Write,
     binding: &crate::MethodBinding,
     method_name: &str,
     args: &[(String, String)],
+    on_companion: bool,
+    bound_classes: &[&str],
+    options: &BindingOptions,
 ) -> std::io::Result<()> {
     let ret = get_return_type(&binding.return_type);
-    let rust_ret = return_type_to_rust_str(ret.clone());
+    let (rust_ret, result) = rust_return(binding, ret.clone(), bound_classes, options);
 
-    write!(file, "    pub fn {}(&'a self", method_name)?;
-    for (name, ty) in args {
-        write!(file, ", {}: {}", name, java_type_to_rust(ty))?;
+    if on_companion {
+        write!(file, "    pub fn {}(", method_name)?;
+        write_arg_params(file, args, options)?;
+    } else {
+        write!(file, "    pub fn {}(&'a self", method_name)?;
+        for (name, ty) in args {
+            write!(file, ", {}: {}", name, java_type_to_rust(ty, options))?;
+        }
     }
     writeln!(file, ") -> Result<{}, JNIError> {{", rust_ret)?;
+    write_arg_conversions(file, args, options)?;
 
     if ret == ReturnType::Primitive(Primitive::Void) {
         writeln!(file, "        call!(")?;
     } else {
         writeln!(file, "        let result = call!(")?;
     }
-    writeln!(file, "            self.inner.as_obj(),")?;
+    writeln!(file, "            env = &mut java(),")?;
+    if on_companion {
+        writeln!(file, "            Self::companion().as_obj(),")?;
+    } else {
+        writeln!(file, "            self.inner.as_obj(),")?;
+    }
     writeln!(file, "            \"{}\",", binding.path)?;
     writeln!(file, "            \"{}\",", binding.name)?;
     writeln!(file, "            \"{}\",", binding.signature)?;
     write!(file, "            &[")?;
-    write_arg_values(file, args)?;
+    write_arg_values(file, args, options)?;
     writeln!(file, "],")?;
     writeln!(file, "            {}", return_type_to_string(ret.clone()))?;
     writeln!(file, "        );")?;
-    writeln!(file, "        Ok({})", unwrap_result(ret))?;
+    writeln!(file, "        Ok({})", result)?;
+    write_arg_conversions_end(file, args)?;
     writeln!(file, "    }}")
 }
 
@@ -258,24 +1311,106 @@ fn write_instance_method(
 // Small helpers
 // ---------------------------------------------------------------------------
 
-fn write_arg_params(file: &mut File, args: &[(String, String)]) -> std::io::Result<()> {
+fn write_arg_params(file: &mut dyn Write, args: &[(String, String)], options: &BindingOptions) -> std::io::Result<()> {
     for (i, (name, ty)) in args.iter().enumerate() {
         if i > 0 { write!(file, ", ")?; }
-        write!(file, "{}: {}", name, java_type_to_rust(ty))?;
+        write!(file, "{}: {}", name, java_type_to_rust(ty, options))?;
     }
     Ok(())
 }
 
-fn write_arg_values(file: &mut File, args: &[(String, String)]) -> std::io::Result<()> {
+fn write_arg_values(file: &mut dyn Write, args: &[(String, String)], options: &BindingOptions) -> std::io::Result<()> {
     for (i, (name, ty)) in args.iter().enumerate() {
         if i > 0 { write!(file, ", ")?; }
-        write!(file, "{}", jvalue_for(name, ty))?;
+        write!(file, "{}", jvalue_for(name, ty, options))?;
+    }
+    Ok(())
+}
+
+/// Statements converting Rust arguments into Java objects before the call,
+/// for parameter types that aren't passed straight through. Arguments only
+/// lent to Java for the call open a scope that the rest of the method body
+/// runs inside, closed by [`write_arg_conversions_end`].
+fn write_arg_conversions(file: &mut dyn Write, args: &[(String, String)], options: &BindingOptions) -> std::io::Result<()> {
+    let conversions: Vec<(&String, String)> = args.iter()
+        .filter_map(|(name, ty)| arg_conversion(name, ty, options).map(|c| (name, c)))
+        .collect();
+    if !conversions.is_empty() || args.iter().any(|(_, ty)| is_scoped_arg(ty)) {
+        // The conversions go through `auto_jni::java()`, which doesn't start the JVM.
+        writeln!(file, "        vm::get_or_init(JVM_OPTIONS)?;")?;
+    }
+    for (name, conversion) in conversions {
+        writeln!(file, "        let {} = {};", name, conversion)?;
+    }
+    for (name, ty) in args {
+        if is_scoped_arg(ty) {
+            writeln!(file, "        auto_jni::buffer::with_borrowed({}, |{}| {{", name, name)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_arg_conversions_end(file: &mut dyn Write, args: &[(String, String)]) -> std::io::Result<()> {
+    for _ in args.iter().filter(|(_, ty)| is_scoped_arg(ty)) {
+        writeln!(file, "        }})?")?;
     }
     Ok(())
 }
 
-fn java_type_to_rust(ty: &str) -> &str {
+/// `ByteBuffer` arguments are only lent to Java for the duration of a call.
+fn is_scoped_arg(ty: &str) -> bool {
+    ty == "Ljava/nio/ByteBuffer"
+}
+
+fn arg_conversion(name: &str, ty: &str, options: &BindingOptions) -> Option<String> {
     match ty {
+        "C" if options.rust_char => Some(format!("auto_jni::chars::to_java_char({})?", name)),
+        "[C" if options.rust_char => Some(format!("auto_jni::chars::array_from_str({})?", name)),
+        "[C" => Some(format!("auto_jni::array::char_array({})?", name)),
+        "Ljava/lang/CharSequence" => Some(format!("auto_jni::chars::char_sequence({})?", name)),
+        "...Ljava/lang/String;" => Some(format!("auto_jni::array::string_array({})?", name)),
+        "...Ljava/lang/Object;" => Some(format!("auto_jni::array::boxed_array({})?", name)),
+        t if t.starts_with("...L") => Some(format!(
+            "auto_jni::array::object_array(\"{}\", {})?",
+            &t[4..t.len() - 1],
+            name
+        )),
+        t if t.starts_with("...") => {
+            let builder = match &t[3..] {
+                "I" => "int_array",
+                "J" => "long_array",
+                "D" => "double_array",
+                "F" => "float_array",
+                "Z" => "boolean_array",
+                "B" => "byte_array",
+                "C" => "char_array",
+                "S" => "short_array",
+                _ => return None,
+            };
+            Some(format!("auto_jni::array::{}({})?", builder, name))
+        }
+        _ => None,
+    }
+}
+
+fn java_type_to_rust(ty: &str, options: &BindingOptions) -> &'static str {
+    match ty {
+        "C" if options.rust_char => "char",
+        "[C" if options.rust_char => "&str",
+        "[C" => "&[u16]",
+        "Ljava/lang/CharSequence" => "&str",
+        "Ljava/nio/ByteBuffer" => "&mut [u8]",
+        "...Ljava/lang/String;" => "&[&str]",
+        "...Ljava/lang/Object;" => "&[JValue]",
+        "...I" => "&[i32]",
+        "...J" => "&[i64]",
+        "...D" => "&[f64]",
+        "...F" => "&[f32]",
+        "...Z" => "&[bool]",
+        "...B" => "&[i8]",
+        "...C" => "&[u16]",
+        "...S" => "&[i16]",
+        t if t.starts_with("...") => "&[&JObject]",
         "I" => "i32",
         "J" => "i64",
         "D" => "f64",
@@ -290,7 +1425,7 @@ fn java_type_to_rust(ty: &str) -> &str {
     }
 }
 
-fn jvalue_for(name: &str, ty: &str) -> String {
+fn jvalue_for(name: &str, ty: &str, options: &BindingOptions) -> String {
     match ty {
         "I" => format!("JValue::Int({}).as_jni()", name),
         "J" => format!("JValue::Long({}).as_jni()", name),
@@ -300,6 +1435,8 @@ fn jvalue_for(name: &str, ty: &str) -> String {
         "B" => format!("JValue::Byte({}).as_jni()", name),
         "C" => format!("JValue::Char({}).as_jni()", name),
         "S" => format!("JValue::Short({}).as_jni()", name),
+        // Converted arguments are owned locals rather than borrowed objects.
+        _ if arg_conversion(name, ty, options).is_some() => format!("JValue::Object(&{}).as_jni()", name),
         _ => format!("JValue::Object({}).as_jni()", name),
     }
 }
@@ -319,6 +1456,50 @@ fn get_return_type(ty: &str) -> ReturnType {
     }
 }
 
+/// Rust return type and the expression converting `result` to it. Some
+/// well-known object types get dedicated wrappers; everything else maps by
+/// `ReturnType`.
+fn rust_return(
+    binding: &crate::MethodBinding,
+    ret: ReturnType,
+    bound_classes: &[&str],
+    options: &BindingOptions,
+) -> (String, String) {
+    match binding.return_type.as_str() {
+        "C" if options.rust_char => (
+            "char".to_string(),
+            "auto_jni::chars::to_char(result.c().unwrap())?".to_string(),
+        ),
+        "[C" if options.rust_char => (
+            "String".to_string(),
+            "auto_jni::chars::string_from_array(&java().auto_local(result.l().unwrap()))?".to_string(),
+        ),
+        "[C" => (
+            "Vec<u16>".to_string(),
+            "auto_jni::chars::utf16_from_array(&java().auto_local(result.l().unwrap()))?".to_string(),
+        ),
+        "Ljava/util/stream/Stream;" => {
+            let item = element_type(&binding.generic_return, "java.util.stream.Stream", bound_classes, options);
+            (
+                format!("auto_jni::stream::JStream<{}>", item),
+                "auto_jni::stream::JStream::new(&java().auto_local(result.l().unwrap()))".to_string(),
+            )
+        }
+        "Ljava/util/concurrent/CompletableFuture;" => {
+            let value = element_type(&binding.generic_return, "java.util.concurrent.CompletableFuture", bound_classes, options);
+            (
+                format!("auto_jni::future::JFuture<{}>", value),
+                "auto_jni::future::JFuture::new(&java().auto_local(result.l().unwrap()))?".to_string(),
+            )
+        }
+        "Ljava/nio/ByteBuffer;" => (
+            "auto_jni::buffer::JavaBytes".to_string(),
+            "auto_jni::buffer::JavaBytes::new(&java().auto_local(result.l().unwrap()))?".to_string(),
+        ),
+        _ => (return_type_to_rust_str(ret.clone()).to_string(), unwrap_result(ret).to_string()),
+    }
+}
+
 fn return_type_to_rust_str(ret: ReturnType) -> &'static str {
     match ret {
         ReturnType::Primitive(Primitive::Int) => "i32",
@@ -330,7 +1511,7 @@ fn return_type_to_rust_str(ret: ReturnType) -> &'static str {
         ReturnType::Primitive(Primitive::Char) => "u16",
         ReturnType::Primitive(Primitive::Short) => "i16",
         ReturnType::Primitive(Primitive::Void) => "()",
-        _ => "JObject<'static>",
+        _ => "AutoLocal<'static, JObject<'static>>",
     }
 }
 
@@ -360,6 +1541,230 @@ fn unwrap_result(ret: ReturnType) -> &'static str {
         ReturnType::Primitive(Primitive::Char) => "result.c().unwrap()",
         ReturnType::Primitive(Primitive::Short) => "result.s().unwrap()",
         ReturnType::Primitive(Primitive::Void) => "()",
-        _ => "result.l().unwrap()",
+        _ => "java().auto_local(result.l().unwrap())",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `write` writes.
+    fn written(write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>) -> String {
+        let mut file = Vec::new();
+        write(&mut file).unwrap();
+        String::from_utf8(file).unwrap()
+    }
+
+    #[test]
+    fn test_rust_ident() {
+        assert_eq!(rust_ident("speed"), "speed");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("match"), "r#match");
+        assert_eq!(rust_ident("self"), "self_");
+        assert_eq!(rust_ident("Self"), "Self_");
+        assert_eq!(rust_ident("reset$fixture"), "reset_fixture");
+    }
+
+    /// `tests/records.rs` compiles and runs the checked-in bindings for a
+    /// record whose components are named `type`, `env`, `obj` and `self`.
+    #[test]
+    fn test_keyword_record_bindings() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let classes = std::env::temp_dir().join(format!("auto-jni-keywords-{}", std::process::id()));
+        let status = std::process::Command::new("javac")
+            .arg("-d")
+            .arg(&classes)
+            .arg(dir.join("tests/fixtures/java/com/fixture/Keywords.java"))
+            .status()
+            .unwrap();
+        assert!(status.success(), "javac failed");
+
+        let generated = classes.join("keywords.rs");
+        generate_bindings_file(
+            vec!["com.fixture.Keywords"],
+            Some(classes.display().to_string()),
+            &generated,
+            Some(vec!["-Djava.class.path=target/auto-jni-fixtures".to_string()]),
+        )
+        .unwrap();
+        let generated = std::fs::read_to_string(generated).unwrap();
+        std::fs::remove_dir_all(&classes).unwrap();
+
+        let checked_in = std::fs::read_to_string(dir.join("tests/fixtures/bindings/keywords.rs")).unwrap();
+        assert!(generated == checked_in, "tests/fixtures/bindings/keywords.rs is out of date");
+    }
+
+    #[test]
+    fn test_binding_sources() {
+        let dir = std::env::temp_dir().join(format!("auto-jni-sources-{}", std::process::id()));
+        let classes = dir.join("classes");
+        let status = std::process::Command::new("javac")
+            .arg("-d")
+            .arg(&classes)
+            .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/java/com/fixture/Keywords.java"))
+            .status()
+            .unwrap();
+        assert!(status.success(), "javac failed");
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+
+        let class_path = std::env::join_paths([dir.join("lib"), dir.join("missing"), classes.clone()]).unwrap();
+        let (_, sources) = generate_bindings_with_sources(
+            vec!["com.fixture.Keywords"],
+            Some(class_path.to_string_lossy().into_owned()),
+            None,
+            &BindingOptions::default(),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sources, [dir.join("lib"), classes.clone(), classes.join("com/fixture/Keywords.class")]);
+    }
+
+    #[test]
+    fn test_kotlin_methods() {
+        let account = crate::tests::parse_fixture("com.fixture.Account");
+        let methods = kotlin_methods(account.methods, account.kotlin.as_ref(), account.companion.as_deref());
+        let methods: Vec<(&str, Option<&str>, bool)> = methods.iter()
+            .map(|(b, name, on_companion)| (b.name.as_str(), name.as_deref(), *on_companion))
+            .collect();
+        // The lambda and the `$default` bridge are dropped, and of the
+        // companion's methods `open` is already bound through `@JvmStatic`.
+        assert_eq!(methods, [
+            ("Account", None, false),
+            ("getBalance", Some("balance"), false),
+            ("setBalance", Some("set_balance"), false),
+            ("isOpen", None, false),
+            ("deposit", None, false),
+            ("getAmount", None, false),
+            ("reset$fixture", None, false),
+            ("audit", None, false),
+            ("open", None, false),
+            ("create", None, true),
+            ("getLimit", Some("limit"), true),
+        ]);
+
+        // Without metadata nothing is renamed or dropped.
+        let varargs = crate::tests::parse_fixture("com.fixture.Varargs");
+        let count = varargs.methods.len();
+        let methods = kotlin_methods(varargs.methods, None, None);
+        assert_eq!(methods.len(), count);
+        assert!(methods.iter().all(|(_, name, on_companion)| name.is_none() && !on_companion));
+    }
+
+    #[test]
+    fn test_kotlin_property_name() {
+        let account = crate::tests::parse_fixture("com.fixture.Account");
+        let kotlin = account.kotlin.as_ref().unwrap();
+        let binding = |name: &str| account.methods.iter().find(|b| b.name == name).unwrap().clone();
+
+        assert_eq!(kotlin_property_name(&binding("getBalance"), kotlin).as_deref(), Some("balance"));
+        assert_eq!(kotlin_property_name(&binding("setBalance"), kotlin).as_deref(), Some("set_balance"));
+        assert_eq!(kotlin_property_name(&binding("isOpen"), kotlin), None);
+        assert_eq!(kotlin_property_name(&binding("deposit"), kotlin), None);
+        assert_eq!(kotlin_property_name(&binding("open"), kotlin), None);
+        // `amount` is in the string table as a parameter name, but it isn't
+        // a property.
+        assert!(kotlin.strings.iter().any(|s| s == "amount"));
+        assert_eq!(kotlin_property_name(&binding("getAmount"), kotlin), None);
+
+        // The setter of an `isOpen` property is `setOpen`.
+        let mut set_open = binding("setBalance");
+        set_open.name = "setOpen".into();
+        set_open.args = vec!["Z".into()];
+        assert_eq!(kotlin_property_name(&set_open, kotlin).as_deref(), Some("set_isOpen"));
+        // A getter the metadata doesn't name isn't a property.
+        let mut get_limit = binding("getBalance");
+        get_limit.name = "getLimit".into();
+        assert_eq!(kotlin_property_name(&get_limit, kotlin), None);
+    }
+
+    #[test]
+    fn test_kotlin_facade_module() {
+        assert_eq!(kotlin_facade_module("com.fixture.StringUtilsKt"), "com_fixture_string_utils");
+        assert_eq!(kotlin_facade_module("MainKt"), "main");
+        assert_eq!(kotlin_facade_module("com.example.IOHelpers"), "com_example_i_o_helpers");
+
+        let facade = crate::tests::parse_fixture("com.fixture.StringUtilsKt");
+        let file = written(|file| {
+            write_class(file, "com.fixture.StringUtilsKt", facade, None, &[], &BindingOptions::default())
+        });
+        assert!(file.contains("pub mod com_fixture_string_utils {"));
+        assert!(file.contains("fn shout"));
+        assert!(file.contains("fn count"));
+        assert!(!file.contains("pub struct"));
+        assert!(!file.contains("lambda"));
+    }
+
+    #[test]
+    fn test_write_companion_accessor() {
+        let file = written(|file| write_companion_accessor(file, "com.fixture.Account", "Companion"));
+        assert!(file.contains("pub fn companion() -> &'static GlobalRef {"));
+        assert!(file.contains(
+            "env.get_static_field(\"com/fixture/Account\", \"Companion\", \"Lcom/fixture/Account$Companion;\")"
+        ));
+
+        let account = crate::tests::parse_fixture("com.fixture.Account");
+        let file = written(|file| {
+            write_class(file, "com.fixture.Account", account, None, &["com.fixture.Account"], &BindingOptions::default())
+        });
+        assert!(file.contains("pub fn companion()"));
+        assert!(file.contains("pub fn limit("));
+        assert!(file.contains("pub fn create("));
+    }
+
+    #[test]
+    fn test_kotlin_synthetic_and_internal_names() {
+        let account = crate::tests::parse_fixture("com.fixture.Account");
+        let file = written(|file| {
+            write_class(file, "com.fixture.Account", account, None, &["com.fixture.Account"], &BindingOptions::default())
+        });
+        assert!(!file.contains("default"));
+        // An `internal` function keeps its mangled name for the call.
+        assert!(file.contains("pub fn reset_fixture(&'a self) -> Result<(), JNIError> {"));
+        assert!(file.contains("            \"reset$fixture\",\n            \"()V\","));
+    }
+
+    #[test]
+    fn test_lambda_names() {
+        let lambda = crate::MethodBinding {
+            path: "com/example/Foo".into(),
+            name: "lambda$run$0".into(),
+            signature: "()V".into(),
+            return_type: "V".into(),
+            is_static: true,
+            ..Default::default()
+        };
+        let file = written(|file| write_methods(file, vec![(lambda, None, false)], &[], &BindingOptions::default()));
+        assert!(file.contains("pub fn run("));
+        assert!(file.contains("\"lambda$run$0\""));
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_describe_kotlin_class() {
+        let lines = describe_class("com.fixture.Account", crate::tests::parse_fixture("com.fixture.Account"));
+        let method = |line: &str| lines.iter().any(|l| l.split_whitespace().collect::<Vec<_>>().join(" ") == line);
+        assert!(method("method balance -> getBalance()I"));
+        // The Rust name is mangled, the Java one isn't.
+        assert!(method("method reset_fixture -> reset$fixture()V"));
+        assert!(method("companion limit -> getLimit()I"));
+        assert!(!lines.iter().any(|l| l.contains("$default") || l.contains("lambda")));
+    }
+
+    #[test]
+    fn test_jni_mangle() {
+        assert_eq!(jni_mangle("com/example/Car"), "com_example_Car");
+        assert_eq!(jni_mangle("com/example/Car$Odd_Name"), "com_example_Car_00024Odd_1Name");
+        assert_eq!(jni_mangle("ünï_x"), "_000fcn_000ef_1x");
+        assert_eq!(jni_mangle("[ILjava/lang/String;"), "_3ILjava_lang_String_2");
+        assert_eq!(jni_mangle("🦀"), "_0d83e_0dd80");
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+        assert_eq!(first_difference("a\nb\n", "a\nc\n"), Some(2));
+        assert_eq!(first_difference("a\nb\n", "a\nb\nc\n"), Some(3));
+        assert_eq!(first_difference("a\nb", "a\nb\n"), Some(3));
     }
 }
@@ -1,14 +1,69 @@
-#[derive(Debug)]
+use thiserror::Error;
+
+#[derive(Debug, Error)]
 pub enum JNIError {
+    #[error("null pointer")]
     NullPtr,
+    #[error("invalid argument")]
     InvalidArg,
+    #[error("unknown JNI error")]
     Unknown,
+    #[error("class not found")]
     NoClass,
+    #[error("method not found")]
     NoMethod,
+    #[error("field not found")]
     NoField,
+    #[error("out of memory")]
     NoMemory,
+    #[error("wrong class type")]
     ClassType,
+    #[error("thread is not attached to the JVM")]
     ThreadDetached,
+    #[error("failed to attach thread to the JVM")]
     AttachFailed,
+    #[error("unknown error")]
     UnknownError,
-}
\ No newline at end of file
+    #[error("Java exception was thrown")]
+    JavaException,
+    #[error("Java exception: {0}")]
+    Exception(String),
+    #[error("a JVM is already running in this process")]
+    AlreadyInitialized,
+    #[error("failed to start the JVM: {0}")]
+    StartFailed(String),
+    #[error("{0:#06x} has no equivalent as both a Java char and a Rust char")]
+    InvalidChar(u32),
+}
+
+impl From<jni::errors::Error> for JNIError {
+    fn from(err: jni::errors::Error) -> Self {
+        use jni::errors::{Error, JniError};
+
+        match err {
+            Error::NullPtr(_) | Error::NullDeref(_) => JNIError::NullPtr,
+            Error::WrongJValueType(..) | Error::InvalidArgList(_) | Error::InvalidCtorReturn => {
+                JNIError::InvalidArg
+            }
+            Error::MethodNotFound { .. } => JNIError::NoMethod,
+            Error::FieldNotFound { .. } => JNIError::NoField,
+            Error::JavaException => JNIError::JavaException,
+            Error::JniCall(JniError::ThreadDetached) => JNIError::ThreadDetached,
+            Error::JniCall(JniError::NoMemory) => JNIError::NoMemory,
+            Error::JniCall(JniError::AlreadyCreated) => JNIError::AlreadyInitialized,
+            _ => JNIError::Unknown,
+        }
+    }
+}
+
+impl From<jni::errors::StartJvmError> for JNIError {
+    fn from(err: jni::errors::StartJvmError) -> Self {
+        JNIError::StartFailed(err.to_string())
+    }
+}
+
+impl From<jni::JvmError> for JNIError {
+    fn from(err: jni::JvmError) -> Self {
+        JNIError::StartFailed(err.to_string())
+    }
+}
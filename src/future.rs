@@ -0,0 +1,292 @@
+//! `java.util.concurrent.CompletableFuture` as a Rust [`Future`].
+//!
+//! A [`JFuture`] registers a small Java callback (`autojni.NativeCallback`,
+//! defined at runtime from bytes embedded in this crate) with
+//! `whenComplete`. Java calls back into Rust when the future completes,
+//! which wakes the task polling the `JFuture`; no thread blocks on `get()`.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use jni::signature::ReturnType;
+use jni::sys::jlong;
+use jni::{JNIEnv, NativeMethod};
+use once_cell::sync::OnceCell;
+
+use crate::errors::JNIError;
+use crate::native;
+use crate::object::{self, FromJava};
+use crate::{call, java};
+
+const CALLBACK_CLASS: &str = "autojni/NativeCallback";
+const CALLBACK_BYTES: &[u8] = include_bytes!("../java/autojni/NativeCallback.class");
+
+#[derive(Default)]
+struct Shared {
+    /// The completed value (possibly a null reference), or the exception's
+    /// `toString()`.
+    outcome: Option<Result<GlobalRef, String>>,
+    waker: Option<Waker>,
+}
+
+/// A `CompletableFuture` whose value converts to `T`.
+///
+/// Resolves to `Err(JNIError::Exception(..))` if the Java future completes
+/// exceptionally. Works with any executor.
+pub struct JFuture<T> {
+    shared: Arc<Mutex<Shared>>,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T: FromJava> JFuture<T> {
+    /// Subscribe to a `java.util.concurrent.CompletableFuture`.
+    pub fn new(future: &JObject) -> Result<Self, JNIError> {
+        let mut env = java();
+        let class = callback_class(&mut env)?;
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let handle = Arc::into_raw(shared.clone()) as jlong;
+
+        let callback = env.new_object(class, "(J)V", &[JValue::Long(handle)]);
+        let callback = match callback {
+            Ok(callback) => env.auto_local(callback),
+            Err(err) => {
+                // Java never saw the handle, so release it here.
+                drop(unsafe { Arc::from_raw(handle as *const Mutex<Shared>) });
+                return Err(err.into());
+            }
+        };
+
+        let stage = call!(
+            env = &mut env,
+            future,
+            "java/util/concurrent/CompletableFuture",
+            "whenComplete",
+            "(Ljava/util/function/BiConsumer;)Ljava/util/concurrent/CompletableFuture;",
+            &[JValue::Object(&callback).as_jni()],
+            ReturnType::Object
+        );
+        env.delete_local_ref(stage.l()?)?;
+
+        Ok(Self {
+            shared,
+            _value: PhantomData,
+        })
+    }
+}
+
+impl<T: FromJava> Future for JFuture<T> {
+    type Output = Result<T, JNIError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.outcome.take() {
+            Some(Ok(value)) => {
+                let mut env = java();
+                let value = env.new_local_ref(&value)?;
+                Poll::Ready(T::from_java(&mut env, value))
+            }
+            Some(Err(message)) => Poll::Ready(Err(JNIError::Exception(message))),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The callback class, defined on first use.
+fn callback_class<'a>(env: &mut JNIEnv<'static>) -> Result<&'a JClass<'static>, JNIError> {
+    static CLASS: OnceCell<GlobalRef> = OnceCell::new();
+
+    let class = CLASS.get_or_try_init(|| -> Result<GlobalRef, JNIError> {
+        let loader = env
+            .call_static_method(
+                "java/lang/ClassLoader",
+                "getSystemClassLoader",
+                "()Ljava/lang/ClassLoader;",
+                &[],
+            )?
+            .l()?;
+        let class = match env.define_class(CALLBACK_CLASS, &loader, CALLBACK_BYTES) {
+            Ok(class) => class,
+            Err(_) => {
+                // Another copy of auto_jni in this process defined it first.
+                env.exception_clear()?;
+                env.find_class(CALLBACK_CLASS)?
+            }
+        };
+        env.register_native_methods(
+            &class,
+            &[NativeMethod {
+                name: "complete".into(),
+                sig: "(JLjava/lang/Object;Ljava/lang/Throwable;)V".into(),
+                fn_ptr: complete as *mut std::ffi::c_void,
+            }],
+        )?;
+        Ok(env.new_global_ref(class)?)
+    })?;
+    Ok(class.as_obj().into())
+}
+
+/// `NativeCallback.complete(long, Object, Throwable)`.
+extern "system" fn complete(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    result: JObject,
+    error: JObject,
+) {
+    native::guard(&mut env, (), |env| {
+        let shared = unsafe { Arc::from_raw(handle as *const Mutex<Shared>) };
+        let outcome = if error.is_null() {
+            env.new_global_ref(&result).map_err(|err| err.to_string())
+        } else {
+            Err(object::to_string(&error).unwrap_or_else(|err| err.to_string()))
+        };
+
+        let waker = {
+            let mut shared = shared.lock().unwrap();
+            shared.outcome = Some(outcome);
+            shared.waker.take()
+        };
+        // Wake without the lock held: executors may poll inline in `wake`.
+        // Wakers are user code and may panic.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+
+    use jni::signature::Primitive;
+
+    use super::*;
+    use crate::create;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        block_on_with(future, Arc::new(ThreadWaker(thread::current())).into())
+    }
+
+    fn block_on_with<F: Future>(future: F, waker: Waker) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    fn complete_later(future: GlobalRef, value: &'static str) {
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            let mut env = java();
+            let value = env.new_string(value).unwrap();
+            call!(
+                env = &mut env,
+                future.as_obj(),
+                "java/util/concurrent/CompletableFuture",
+                "complete",
+                "(Ljava/lang/Object;)Z",
+                &[JValue::Object(&value).as_jni()],
+                ReturnType::Primitive(Primitive::Boolean)
+            );
+        });
+    }
+
+    #[test]
+    fn test_completes_later() {
+        crate::vm::test_jvm();
+
+        let future = create!("java/util/concurrent/CompletableFuture", "()V", &[]);
+        let value = JFuture::<String>::new(future.as_obj()).unwrap();
+        complete_later(future, "done");
+
+        assert_eq!(block_on(value).unwrap(), "done");
+    }
+
+    /// A waker that takes the future's lock, as an executor polling inline
+    /// in `wake` would.
+    struct LockingWaker(Arc<Mutex<Shared>>, Thread);
+
+    impl Wake for LockingWaker {
+        fn wake(self: Arc<Self>) {
+            drop(self.0.lock().unwrap());
+            self.1.unpark();
+        }
+    }
+
+    #[test]
+    fn test_wake_polls_inline() {
+        crate::vm::test_jvm();
+
+        let future = create!("java/util/concurrent/CompletableFuture", "()V", &[]);
+        let value = JFuture::<String>::new(future.as_obj()).unwrap();
+        let waker = Arc::new(LockingWaker(value.shared.clone(), thread::current())).into();
+        complete_later(future, "inline");
+
+        assert_eq!(block_on_with(value, waker).unwrap(), "inline");
+    }
+
+    #[test]
+    fn test_already_completed_and_failed() {
+        let mut env = crate::vm::test_jvm().env();
+
+        let value = env.new_string("now").unwrap();
+        let done = env
+            .call_static_method(
+                "java/util/concurrent/CompletableFuture",
+                "completedFuture",
+                "(Ljava/lang/Object;)Ljava/util/concurrent/CompletableFuture;",
+                &[JValue::Object(&value)],
+            )
+            .unwrap()
+            .l()
+            .unwrap();
+        assert_eq!(block_on(JFuture::<String>::new(&done).unwrap()).unwrap(), "now");
+
+        let failed = create!("java/util/concurrent/CompletableFuture", "()V", &[]);
+        let message = env.new_string("boom").unwrap();
+        let error = env
+            .new_object(
+                "java/lang/IllegalStateException",
+                "(Ljava/lang/String;)V",
+                &[JValue::Object(&message)],
+            )
+            .unwrap();
+        let value = JFuture::<String>::new(failed.as_obj()).unwrap();
+        env.call_method(
+            failed.as_obj(),
+            "completeExceptionally",
+            "(Ljava/lang/Throwable;)Z",
+            &[JValue::Object(&error)],
+        )
+        .unwrap();
+
+        match block_on(value) {
+            Err(JNIError::Exception(message)) => {
+                assert_eq!(message, "java.lang.IllegalStateException: boom")
+            }
+            other => panic!("expected an exception, got {:?}", other.map(|_| ())),
+        }
+    }
+}
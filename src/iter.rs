@@ -0,0 +1,139 @@
+//! Rust iterators over Java `Iterator`s and `Iterable`s.
+
+use std::marker::PhantomData;
+
+use jni::objects::{AutoLocal, GlobalRef, JObject};
+use jni::signature::{Primitive, ReturnType};
+
+use crate::object::FromJava;
+use crate::{call, java};
+
+/// A `java.util.Iterator` driven as a Rust [`Iterator`].
+///
+/// Each element returned by `next()` is converted with [`FromJava`]; use
+/// `AutoLocal<'static, JObject<'static>>` when the element type is unknown.
+/// Panics if a JNI call fails or the conversion does, like generated methods.
+pub struct JIterator<T = AutoLocal<'static, JObject<'static>>> {
+    inner: GlobalRef,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: FromJava> JIterator<T> {
+    /// Wrap a `java.util.Iterator`.
+    pub fn new(iterator: &JObject) -> Self {
+        Self {
+            inner: java().new_global_ref(iterator).unwrap(),
+            _item: PhantomData,
+        }
+    }
+
+    /// Call `iterable.iterator()` on a `java.lang.Iterable`.
+    pub fn from_iterable(iterable: &JObject) -> Self {
+        let mut env = java();
+        let iterator = call!(
+            env = &mut env,
+            iterable,
+            "java/lang/Iterable",
+            "iterator",
+            "()Ljava/util/Iterator;",
+            &[],
+            ReturnType::Object
+        );
+        let iterator = env.auto_local(iterator.l().unwrap());
+        Self::new(&iterator)
+    }
+
+    pub fn inner(&self) -> &GlobalRef {
+        &self.inner
+    }
+}
+
+impl<T: FromJava> Iterator for JIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut env = java();
+        let has_next = call!(
+            env = &mut env,
+            self.inner.as_obj(),
+            "java/util/Iterator",
+            "hasNext",
+            "()Z",
+            &[],
+            ReturnType::Primitive(Primitive::Boolean)
+        );
+        if !has_next.z().unwrap() {
+            return None;
+        }
+        let next = call!(
+            env = &mut env,
+            self.inner.as_obj(),
+            "java/util/Iterator",
+            "next",
+            "()Ljava/lang/Object;",
+            &[],
+            ReturnType::Object
+        );
+        Some(T::from_java(&mut env, next.l().unwrap()).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jni::objects::JValue;
+
+    use super::*;
+    use crate::create;
+
+    fn array_list(items: &[JObject]) -> GlobalRef {
+        let mut env = java();
+        let list = create!(env = &mut env, "java/util/ArrayList", "()V", &[]);
+        for item in items {
+            call!(
+                env = &mut env,
+                list.as_obj(),
+                "java/util/ArrayList",
+                "add",
+                "(Ljava/lang/Object;)Z",
+                &[JValue::Object(item).as_jni()],
+                ReturnType::Primitive(Primitive::Boolean)
+            );
+        }
+        list
+    }
+
+    #[test]
+    fn test_iterate_strings() {
+        let env = crate::vm::test_jvm().env();
+        let a = env.new_string("a").unwrap();
+        let b = env.new_string("b").unwrap();
+        let list = array_list(&[a.into(), JObject::null(), b.into()]);
+
+        let items: Vec<Option<String>> = JIterator::from_iterable(list.as_obj()).collect();
+        assert_eq!(items, vec![Some("a".to_string()), None, Some("b".to_string())]);
+
+        assert_eq!(JIterator::<AutoLocal<JObject>>::from_iterable(list.as_obj()).count(), 3);
+    }
+
+    #[test]
+    fn test_iterate_boxed() {
+        let mut env = crate::vm::test_jvm().env();
+        let boxed: Vec<JObject> = (1..=3)
+            .map(|i| {
+                env.call_static_method(
+                    "java/lang/Integer",
+                    "valueOf",
+                    "(I)Ljava/lang/Integer;",
+                    &[JValue::Int(i)],
+                )
+                .unwrap()
+                .l()
+                .unwrap()
+            })
+            .collect();
+        let list = array_list(&boxed);
+
+        let sum: i32 = JIterator::<i32>::from_iterable(list.as_obj()).sum();
+        assert_eq!(sum, 6);
+    }
+}
@@ -0,0 +1,349 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use regex::Regex;
+
+/// A Java class whose methods are implemented in Rust, for
+/// [`generate_java_source`].
+///
+/// Methods are declared as they would be in Java, without `native` or an
+/// access modifier:
+/// ```
+/// let calc = auto_jni::NativeClass::new("com.example.Calc", "calc")
+///     .method("int add(int a, int b)")
+///     .method("static String greet(String name)");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeClass {
+    /// Fully-qualified name, e.g. `com.example.Calc`.
+    pub class: String,
+    /// Library passed to `System.loadLibrary` when the class is loaded:
+    /// `calc` for `libcalc.so`, `libcalc.dylib` or `calc.dll`.
+    pub library: String,
+    pub methods: Vec<String>,
+}
+
+impl NativeClass {
+    pub fn new(class: &str, library: &str) -> Self {
+        Self {
+            class: class.to_string(),
+            library: library.to_string(),
+            methods: Vec::new(),
+        }
+    }
+
+    /// Add a method implemented in Rust, e.g. `static long twice(long x)`.
+    pub fn method(mut self, declaration: &str) -> Self {
+        self.methods.push(declaration.to_string());
+        self
+    }
+}
+
+/// A parsed method declaration.
+#[derive(Debug, PartialEq)]
+struct Declaration {
+    is_static: bool,
+    return_type: String,
+    name: String,
+    params: Vec<(String, String)>,
+}
+
+/// Write `{dir}/com/example/Calc.java` for `class` and return the path
+/// written. The class loads its library in a static initializer, and each
+/// method is a `public` wrapper that rejects `null` references before
+/// calling a `private native` stub named after it: `add` calls `add0`.
+///
+/// Compile the result with [`compile_java`] and pass the class to
+/// [`generate_bindings_file`](crate::generate_bindings_file) to get the
+/// Rust side (its `NativeFns` table or `_Natives` trait), so the two
+/// can't drift apart.
+pub fn generate_java_source(class: &NativeClass, dir: &Path) -> std::io::Result<PathBuf> {
+    let (package, simple_name) = match class.class.rsplit_once('.') {
+        Some((package, name)) => (Some(package), name),
+        None => (None, class.class.as_str()),
+    };
+    // Nested classes live in their outer class's source file.
+    if class.class.contains('$')
+        || !is_identifier(simple_name)
+        || package.is_some_and(|p| !p.split('.').all(is_identifier))
+    {
+        return Err(invalid(format!("`{}` is not a top-level Java class name", class.class)));
+    }
+    let methods = class.methods.iter()
+        .map(|m| parse_declaration(m).ok_or_else(|| invalid(format!("can't parse method declaration `{}`", m))))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(method) = methods.iter().find(|m| methods.iter().any(|other| other.name == native_name(m))) {
+        return Err(invalid(format!("`{}` clashes with the native stub of `{}`", native_name(method), method.name)));
+    }
+
+    let mut source = String::new();
+    source.push_str("// Generated by auto-jni. Do not edit.\n");
+    if let Some(package) = package {
+        source.push_str(&format!("package {};\n", package));
+    }
+    source.push('\n');
+    source.push_str(&format!("public class {} {{\n", simple_name));
+    source.push_str("    static {\n");
+    source.push_str(&format!("        System.loadLibrary(\"{}\");\n", java_escape(&class.library)));
+    source.push_str("    }\n");
+    for method in &methods {
+        let params: Vec<String> = method.params.iter().map(|(ty, name)| format!("{} {}", ty, name)).collect();
+        let args: Vec<&str> = method.params.iter().map(|(_, name)| name.as_str()).collect();
+        let modifiers = if method.is_static { "static " } else { "" };
+        source.push('\n');
+        source.push_str(&format!(
+            "    public {}{} {}({}) {{\n",
+            modifiers,
+            method.return_type,
+            method.name,
+            params.join(", ")
+        ));
+        for (ty, name) in &method.params {
+            if !is_primitive(ty) {
+                source.push_str(&format!("        java.util.Objects.requireNonNull({}, \"{}\");\n", name, name));
+            }
+        }
+        source.push_str(&format!(
+            "        {}{}({});\n",
+            if method.return_type == "void" { "" } else { "return " },
+            native_name(method),
+            args.join(", ")
+        ));
+        source.push_str("    }\n");
+        source.push('\n');
+        source.push_str(&format!(
+            "    private {}native {} {}({});\n",
+            modifiers,
+            method.return_type,
+            native_name(method),
+            params.join(", ")
+        ));
+    }
+    source.push_str("}\n");
+
+    let mut path = dir.to_path_buf();
+    path.extend(package.into_iter().flat_map(|p| p.split('.')));
+    fs::create_dir_all(&path)?;
+    path.push(format!("{}.java", simple_name));
+    fs::write(&path, source)?;
+    Ok(path)
+}
+
+/// Compile Java sources into `out` with `javac`.
+pub fn compile_java(sources: &[PathBuf], out: &Path) -> std::io::Result<()> {
+    let output = Command::new("javac")
+        .args(["-encoding", "UTF-8", "-d"])
+        .arg(out)
+        .args(sources)
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::other(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    Ok(())
+}
+
+/// `s` escaped for a Java string literal, with everything outside
+/// printable ASCII as `\uXXXX` UTF-16 units.
+fn java_escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            // javac turns `\u000a` into a line break before reading the
+            // literal, so line terminators need their own escapes.
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            ' '..='~' => escaped.push(c),
+            _ => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+    }
+    escaped
+}
+
+/// The `native` stub a generated wrapper calls.
+fn native_name(method: &Declaration) -> String {
+    format!("{}0", method.name)
+}
+
+fn is_primitive(ty: &str) -> bool {
+    matches!(ty, "boolean" | "byte" | "char" | "short" | "int" | "long" | "float" | "double")
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `[static] Type name(Type a, Type b)`, where types may be qualified,
+/// generic or arrays. Other modifiers are rejected: the generated wrapper
+/// is always `public`.
+fn parse_declaration(declaration: &str) -> Option<Declaration> {
+    let re = Regex::new(r"^\s*(static\s+)?([\w.$<>,?\[\] ]+?)\s+([\w$]+)\s*\((.*)\)\s*;?\s*$").unwrap();
+    let caps = re.captures(declaration)?;
+    let name = caps[3].to_string();
+    if !is_identifier(&name) || !is_type(&caps[2]) {
+        return None;
+    }
+
+    let mut params = Vec::new();
+    for param in split_params(&caps[4]) {
+        let (ty, name) = param.trim().rsplit_once(char::is_whitespace)?;
+        if !is_identifier(name) || !is_type(ty.trim()) {
+            return None;
+        }
+        params.push((ty.trim().to_string(), name.to_string()));
+    }
+
+    Some(Declaration {
+        is_static: caps.get(1).is_some(),
+        return_type: caps[2].trim().to_string(),
+        name,
+        params,
+    })
+}
+
+/// A type as written in a declaration: no modifier keywords, and no spaces
+/// outside `<...>` (so `public int` or `final String` aren't types).
+fn is_type(ty: &str) -> bool {
+    const MODIFIERS: &[&str] = &[
+        "public", "protected", "private", "static", "final", "synchronized", "native", "abstract",
+        "strictfp", "transient", "volatile", "default",
+    ];
+    let mut depth = 0;
+    for c in ty.trim().chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => return false,
+            _ => {}
+        }
+    }
+    !ty.trim().is_empty() && !MODIFIERS.contains(&ty.trim())
+}
+
+/// Split a parameter list at the commas that aren't inside `<...>`.
+fn split_params(params: &str) -> Vec<&str> {
+    if params.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut split = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(&params[start..]);
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_declaration() {
+        assert_eq!(
+            parse_declaration("static Map<String, Integer> count(List<String> words, int[] skip)"),
+            Some(Declaration {
+                is_static: true,
+                return_type: "Map<String, Integer>".into(),
+                name: "count".into(),
+                params: vec![("List<String>".into(), "words".into()), ("int[]".into(), "skip".into())],
+            })
+        );
+        assert_eq!(parse_declaration("void reset()").unwrap().params, vec![]);
+        assert_eq!(parse_declaration("int ünï(int x);").unwrap().name, "ünï");
+        assert_eq!(parse_declaration("int add(int, int)"), None);
+        assert_eq!(parse_declaration("add(int a)"), None);
+
+        // Only `static` is accepted as a modifier.
+        assert_eq!(parse_declaration("public int add(int a)"), None);
+        assert_eq!(parse_declaration("private static int add(int a)"), None);
+        assert_eq!(parse_declaration("static final int add(int a)"), None);
+        assert_eq!(parse_declaration("synchronized void reset()"), None);
+        assert_eq!(parse_declaration("final add(int a)"), None);
+        assert_eq!(parse_declaration("int add(final int a)"), None);
+        assert_eq!(parse_declaration("java.util.List <String> words()"), None);
+        assert_eq!(parse_declaration("Map<String, List<Integer>> index()").unwrap().return_type, "Map<String, List<Integer>>");
+    }
+
+    #[test]
+    fn test_generate_java_source() {
+        let dir = std::env::temp_dir().join(format!("auto-jni-javagen-{}", std::process::id()));
+        let calc = NativeClass::new("com.example.Calc", "calc")
+            .method("int add(int a, int b)")
+            .method("static String greet(String name)")
+            .method("void fill(int[] values, int value)");
+
+        let path = generate_java_source(&calc, &dir).unwrap();
+        assert_eq!(path, dir.join("com/example/Calc.java"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "// Generated by auto-jni. Do not edit.\n\
+             package com.example;\n\
+             \n\
+             public class Calc {\n    \
+                 static {\n        \
+                     System.loadLibrary(\"calc\");\n    \
+                 }\n\
+             \n    \
+                 public int add(int a, int b) {\n        \
+                     return add0(a, b);\n    \
+                 }\n\
+             \n    \
+                 private native int add0(int a, int b);\n\
+             \n    \
+                 public static String greet(String name) {\n        \
+                     java.util.Objects.requireNonNull(name, \"name\");\n        \
+                     return greet0(name);\n    \
+                 }\n\
+             \n    \
+                 private static native String greet0(String name);\n\
+             \n    \
+                 public void fill(int[] values, int value) {\n        \
+                     java.util.Objects.requireNonNull(values, \"values\");\n        \
+                     fill0(values, value);\n    \
+                 }\n\
+             \n    \
+                 private native void fill0(int[] values, int value);\n\
+             }\n"
+        );
+        compile_java(&[path], &dir.join("classes")).unwrap();
+
+        let clash = NativeClass::new("com.example.Clash", "calc")
+            .method("int add(int a)")
+            .method("int add0(int a)");
+        assert_eq!(generate_java_source(&clash, &dir).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // javac only reads `\uXXXX` escapes.
+        let odd = NativeClass::new("com.example.Odd", "ünï\"\\\n\t🦀");
+        let path = generate_java_source(&odd, &dir).unwrap();
+        assert!(fs::read_to_string(&path).unwrap()
+            .contains(r#"System.loadLibrary("\u00fcn\u00ef\"\\\n\u0009\ud83e\udd80");"#));
+        compile_java(&[path], &dir.join("classes")).unwrap();
+
+        let broken = NativeClass::new("com.example.Calc$Inner", "calc");
+        assert_eq!(generate_java_source(&broken, &dir).unwrap_err().kind(), ErrorKind::InvalidInput);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
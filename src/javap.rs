@@ -0,0 +1,447 @@
+//! Running `javap` and parsing its output, cached by the content of the
+//! class.
+//!
+//! Starting `javap` costs a JVM startup per class, and parsing its output
+//! runs every regex in [`crate::parse_javap`] over it. The parsed class is
+//! kept in memory for the rest of the process and, in build scripts, in
+//! `OUT_DIR` across builds, next to javap's output. Entries are keyed by a
+//! hash of the class file, or of the jars on the class path when the class
+//! isn't in a directory, so a class that hasn't changed is neither run
+//! through `javap` nor parsed again. javap's output outlives the parsed
+//! form, which is also keyed by the auto-jni version: a new parser only
+//! reparses.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::{Companion, JavaClass, KotlinMetadata, MethodBinding, RecordComponent};
+
+/// Serialized classes (see [`serialize`]) by class name, with their key.
+static CACHE: Lazy<Mutex<HashMap<String, (u64, String)>>> = Lazy::new(Default::default);
+
+/// The parsed `javap -v -p` output for `class_name`, or an error if javap
+/// couldn't read the class.
+pub(crate) fn class(class_name: &str, class_path: Option<&str>) -> io::Result<JavaClass> {
+    let cache_dir = std::env::var_os("OUT_DIR").map(|out| Path::new(&out).join("auto-jni-javap"));
+    cached_class(class_name, class_path, cache_dir.as_deref())
+}
+
+/// [`class`], with the cache files in `cache_dir`.
+fn cached_class(class_name: &str, class_path: Option<&str>, cache_dir: Option<&Path>) -> io::Result<JavaClass> {
+    let Some(key) = class_path.and_then(|cp| content_hash(class_name, cp)) else {
+        let output = run(class_name, class_path)?;
+        return crate::parse_javap(class_name, &output, &mut |companion| cached_class(companion, class_path, cache_dir));
+    };
+    let cache_file = |extension: &str| cache_dir.map(|dir| dir.join(format!("{}.{}", class_name, extension)));
+    let output_header = format!("{:016x}", key);
+    let class_header = format!("{:016x} {}", key, env!("CARGO_PKG_VERSION"));
+
+    let cache = || CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    let in_memory = cache().get(class_name).filter(|(k, _)| *k == key).map(|(_, class)| class.clone());
+    let cached = in_memory
+        .or_else(|| read_cache_file(cache_file("parsed").as_deref(), &class_header))
+        .and_then(|serialized| Some((deserialize(&serialized)?, serialized)));
+    if let Some((mut java_class, serialized)) = cached {
+        // The companion's class file is cached on its own.
+        if let Some(companion) = &mut java_class.companion {
+            companion.class = cached_class(&format!("{}${}", class_name, companion.field), class_path, cache_dir)?;
+        }
+        cache().insert(class_name.to_string(), (key, serialized));
+        return Ok(java_class);
+    }
+
+    let output = match read_cache_file(cache_file("txt").as_deref(), &output_header) {
+        Some(output) => output,
+        None => {
+            let output = run(class_name, class_path)?;
+            write_cache_file(cache_file("txt").as_deref(), &output_header, &output);
+            output
+        }
+    };
+    let java_class = crate::parse_javap(class_name, &output, &mut |companion| {
+        cached_class(companion, class_path, cache_dir)
+    })?;
+    if let Some(serialized) = serialize(&java_class) {
+        write_cache_file(cache_file("parsed").as_deref(), &class_header, &serialized);
+        cache().insert(class_name.to_string(), (key, serialized));
+    }
+    Ok(java_class)
+}
+
+/// The content of a cache file after its first line, if that's `header`.
+fn read_cache_file(file: Option<&Path>, header: &str) -> Option<String> {
+    let cached = fs::read_to_string(file?).ok()?;
+    let (file_header, content) = cached.split_once('\n')?;
+    (file_header == header).then(|| content.to_string())
+}
+
+fn write_cache_file(file: Option<&Path>, header: &str, content: &str) {
+    if let Some(file) = file {
+        // A missing cache entry only costs a `javap` run next time.
+        let _ = fs::create_dir_all(file.parent().unwrap());
+        let _ = fs::write(file, format!("{}\n{}", header, content));
+    }
+}
+
+fn run(class_name: &str, class_path: Option<&str>) -> io::Result<String> {
+    let mut command = Command::new("javap");
+    // -v prints each method's descriptor and access flags. Names and
+    // Kotlin metadata can be non-ASCII, so don't let javap fall back to
+    // the platform encoding.
+    command.args(["-v", "-p", "-J-Dfile.encoding=UTF-8", "-J-Dstdout.encoding=UTF-8"]);
+
+    if let Some(cp) = class_path {
+        command.arg("-classpath").arg(cp);
+    }
+
+    command.arg(class_name);
+
+    let output = command.output()
+        .map_err(|err| io::Error::new(err.kind(), format!("failed to run javap (is a JDK on PATH?): {}", err)))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    // javap exits with 0 for some failures, but always names the class
+    // file it read.
+    if !output.status.success() || !stdout.lines().any(|line| line.starts_with("Classfile ")) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.trim() {
+            "" => "no class file in its output",
+            reason => reason.strip_prefix("Error: ").unwrap_or(reason),
+        };
+        return Err(io::Error::other(format!("javap couldn't read `{}`: {}", class_name, reason)));
+    }
+    Ok(stdout)
+}
+
+/// A hash of what `javap` would read for `class_name`: the first class file
+/// for it in a class path directory, or else every jar on the class path.
+/// `None` if there's neither, e.g. for JDK classes with a directory class
+/// path.
+fn content_hash(class_name: &str, class_path: &str) -> Option<u64> {
+    let class_file = format!("{}.class", class_name.replace('.', "/"));
+    let mut hash = Fnv::new();
+    hash.write(class_name.as_bytes());
+    hash.write(class_path.as_bytes());
+    // The JDK classes that aren't on the class path.
+    hash.write(std::env::var("JAVA_HOME").unwrap_or_default().as_bytes());
+
+    let mut found = false;
+    for entry in std::env::split_paths(class_path) {
+        let path = entry.join(&class_file);
+        if entry.is_dir() && path.is_file() {
+            // Which directory, for a class file added earlier on the class
+            // path with the same content.
+            hash.write(path.to_string_lossy().as_bytes());
+            hash.write(&fs::read(path).ok()?);
+            return Some(hash.finish());
+        }
+        if entry.is_file() {
+            hash.write(&fs::read(entry).ok()?);
+            found = true;
+        }
+    }
+    found.then(|| hash.finish())
+}
+
+/// FNV-1a, so cache keys stay the same across Rust versions.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+        // Separate consecutive writes.
+        self.0 = (self.0 ^ 0xff).wrapping_mul(0x100000001b3);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// `java_class` as one tab-separated line per item. A companion is written
+/// as its field name only, since its class is cached separately. `None` if
+/// the class file's path isn't UTF-8.
+fn serialize(java_class: &JavaClass) -> Option<String> {
+    let mut lines = Vec::new();
+    if let Some(source) = &java_class.source {
+        lines.push(format!("source\t{}", escape(source.to_str()?)));
+    }
+    for method in &java_class.methods {
+        let flags: String = [
+            (method.is_static, 's'),
+            (method.is_constructor, 'c'),
+            (method.is_varargs, 'v'),
+            (method.is_synthetic, 'y'),
+            (method.is_native, 'n'),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| flag)
+        .collect();
+        let fields = [&method.path, &method.name, &method.signature, &method.return_type, &method.generic_return]
+            .into_iter()
+            .chain(&method.args)
+            .map(|field| escape(field))
+            .collect::<Vec<_>>();
+        lines.push(format!("method\t{}\t{}", flags, fields.join("\t")));
+    }
+    if let Some(components) = &java_class.record {
+        lines.push("record".to_string());
+        for component in components {
+            lines.push(format!("component\t{}\t{}", escape(&component.name), escape(&component.descriptor)));
+        }
+    }
+    for subclass in &java_class.permitted_subclasses {
+        lines.push(format!("permitted\t{}", escape(subclass)));
+    }
+    if let Some(kotlin) = &java_class.kotlin {
+        lines.push(format!("kotlin\t{}", kotlin.kind));
+        lines.extend(kotlin.strings.iter().map(|s| format!("string\t{}", escape(s))));
+        lines.extend(kotlin.properties.iter().map(|p| format!("property\t{}", escape(p))));
+    }
+    if let Some(companion) = &java_class.companion {
+        lines.push(format!("companion\t{}", escape(&companion.field)));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Undo [`serialize`], with an empty class for the companion. `None` if
+/// `serialized` is malformed.
+fn deserialize(serialized: &str) -> Option<JavaClass> {
+    let mut java_class = JavaClass::default();
+    for line in serialized.lines() {
+        let mut fields = line.split('\t').map(unescape);
+        match fields.next()?.as_str() {
+            "source" => java_class.source = Some(PathBuf::from(fields.next()?)),
+            "method" => {
+                let flags = fields.next()?;
+                java_class.methods.push(MethodBinding {
+                    path: fields.next()?,
+                    name: fields.next()?,
+                    signature: fields.next()?,
+                    return_type: fields.next()?,
+                    generic_return: fields.next()?,
+                    args: fields.by_ref().collect(),
+                    is_static: flags.contains('s'),
+                    is_constructor: flags.contains('c'),
+                    is_varargs: flags.contains('v'),
+                    is_synthetic: flags.contains('y'),
+                    is_native: flags.contains('n'),
+                });
+            }
+            "record" => java_class.record = Some(Vec::new()),
+            "component" => java_class.record.as_mut()?.push(RecordComponent {
+                name: fields.next()?,
+                descriptor: fields.next()?,
+            }),
+            "permitted" => java_class.permitted_subclasses.push(fields.next()?),
+            "kotlin" => {
+                let kind = fields.next()?.parse().ok()?;
+                java_class.kotlin = Some(KotlinMetadata { kind, strings: Vec::new(), properties: Vec::new() });
+            }
+            "string" => java_class.kotlin.as_mut()?.strings.push(fields.next()?),
+            "property" => java_class.kotlin.as_mut()?.properties.push(fields.next()?),
+            "companion" => {
+                java_class.companion = Some(Box::new(Companion { field: fields.next()?, class: JavaClass::default() }));
+            }
+            _ => return None,
+        }
+        if fields.next().is_some() {
+            return None;
+        }
+    }
+    Some(java_class)
+}
+
+/// Escape the separators [`serialize`] uses.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+/// The file javap read a class from, from its `Classfile` line: the class
+/// file, or the jar containing it. `None` for classes in the JDK's modules.
+pub(crate) fn classfile_source(line: &str) -> Option<PathBuf> {
+    let location = line.strip_prefix("Classfile ")?;
+    let Some(url) = location.strip_prefix("jar:file:") else {
+        return (!location.starts_with("jrt:")).then(|| PathBuf::from(location));
+    };
+    let jar = url.split_once("!/").map_or(url, |(jar, _)| jar);
+    let jar = percent_decode(jar.trim_start_matches("//"));
+    // `/C:/lib/x.jar` on Windows.
+    let jar = match jar.as_bytes() {
+        [b'/', _, b':', ..] => jar[1..].to_string(),
+        _ => jar,
+    };
+    Some(PathBuf::from(jar))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.filter(|_| bytes[i] == b'%').and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::parse_fixture;
+
+    #[test]
+    fn test_classfile_source() {
+        assert_eq!(
+            classfile_source("Classfile /work/java/com/example/Car.class"),
+            Some(PathBuf::from("/work/java/com/example/Car.class"))
+        );
+        assert_eq!(
+            classfile_source("Classfile jar:file:///work/my%20lib.jar!/com/example/Car.class"),
+            Some(PathBuf::from("/work/my lib.jar"))
+        );
+        assert_eq!(
+            classfile_source("Classfile jar:file:/C:/lib/ünï.jar!/com/example/Car.class"),
+            Some(PathBuf::from("C:/lib/ünï.jar"))
+        );
+        assert_eq!(classfile_source("Classfile jrt:/java.base/java/lang/String.class"), None);
+        assert_eq!(classfile_source("  Compiled from \"Car.java\""), None);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let dir = std::env::temp_dir().join(format!("auto-jni-javap-{}", std::process::id()));
+        let classes = dir.join("classes");
+        fs::create_dir_all(classes.join("com/example")).unwrap();
+        let cp = classes.to_str().unwrap();
+
+        assert_eq!(content_hash("com.example.Car", cp), None);
+        fs::write(classes.join("com/example/Car.class"), [0xca, 0xfe]).unwrap();
+        let first = content_hash("com.example.Car", cp).unwrap();
+        assert_eq!(content_hash("com.example.Car", cp), Some(first));
+        fs::write(classes.join("com/example/Car.class"), [0xca, 0xfe, 0]).unwrap();
+        assert_ne!(content_hash("com.example.Car", cp), Some(first));
+
+        // Only the jars are known for classes that aren't in a directory.
+        let jar = dir.join("lib.jar");
+        fs::write(&jar, [1, 2]).unwrap();
+        let cp = std::env::join_paths([&classes, &jar]).unwrap();
+        let cp = cp.to_str().unwrap();
+        let in_jar = content_hash("com.example.Engine", cp).unwrap();
+        fs::write(&jar, [1, 3]).unwrap();
+        assert_ne!(content_hash("com.example.Engine", cp), Some(in_jar));
+
+        // The same class file, shadowing the other from earlier on the
+        // class path.
+        let shadow = dir.join("shadow");
+        fs::create_dir_all(shadow.join("com/example")).unwrap();
+        let cp = std::env::join_paths([&shadow, &classes]).unwrap();
+        let cp = cp.to_str().unwrap();
+        let shadowed = content_hash("com.example.Car", cp).unwrap();
+        fs::copy(classes.join("com/example/Car.class"), shadow.join("com/example/Car.class")).unwrap();
+        assert_ne!(content_hash("com.example.Car", cp), Some(shadowed));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        for class_name in [
+            "com.fixture.Account",
+            "com.fixture.Keywords",
+            "com.fixture.Natives",
+            "com.fixture.Shape",
+            "com.fixture.StringUtilsKt",
+            "com.fixture.Varargs",
+        ] {
+            let mut java_class = parse_fixture(class_name);
+            java_class.source = Some(PathBuf::from("/work/classes\twith\\tabs/Fixture.class"));
+            let serialized = serialize(&java_class).unwrap();
+            let mut expected = java_class;
+            if let Some(companion) = &mut expected.companion {
+                companion.class = JavaClass::default();
+            }
+            assert_eq!(deserialize(&serialized), Some(expected), "{}", class_name);
+        }
+        assert_eq!(deserialize("method\ts"), None);
+        assert_eq!(deserialize("string\tbalance"), None);
+    }
+
+    #[test]
+    fn test_cached_class() {
+        let dir = std::env::temp_dir().join(format!("auto-jni-javap-cache-{}", std::process::id()));
+        let classes = dir.join("classes");
+        let cache_dir = dir.join("cache");
+        let compile = |source: &str| {
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(dir.join("src/Cached.java"), source).unwrap();
+            let status = Command::new("javac")
+                .arg("-d")
+                .arg(&classes)
+                .arg(dir.join("src/Cached.java"))
+                .status()
+                .unwrap();
+            assert!(status.success(), "javac failed");
+        };
+        let names = |java_class: &JavaClass| java_class.methods.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+        let cp = classes.to_str().unwrap();
+
+        compile("package cachetest; public class Cached { public void first() {} }");
+        let java_class = cached_class("cachetest.Cached", Some(cp), Some(&cache_dir)).unwrap();
+        assert_eq!(names(&java_class), ["Cached", "first"]);
+        assert!(cache_dir.join("cachetest.Cached.txt").is_file());
+
+        // An unchanged class comes from the cache file, without javap or
+        // the parser.
+        let parsed_file = cache_dir.join("cachetest.Cached.parsed");
+        let cached = fs::read_to_string(&parsed_file).unwrap();
+        fs::write(&parsed_file, cached.replace("\tfirst\t", "\tfromCache\t")).unwrap();
+        CACHE.lock().unwrap().remove("cachetest.Cached");
+        let java_class = cached_class("cachetest.Cached", Some(cp), Some(&cache_dir)).unwrap();
+        assert_eq!(names(&java_class), ["Cached", "fromCache"]);
+
+        compile("package cachetest; public class Cached { public void first() {} public void second() {} }");
+        let java_class = cached_class("cachetest.Cached", Some(cp), Some(&cache_dir)).unwrap();
+        assert_eq!(names(&java_class), ["Cached", "first", "second"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,67 +1,303 @@
+pub mod array;
+pub mod buffer;
+pub mod chars;
 pub mod errors;
+pub mod future;
+pub mod iter;
+pub mod native;
+pub mod object;
+pub mod signature;
+pub mod stream;
+pub mod vm;
 mod call;
 
+pub use object::{FromJava, IntoJava, JavaObject};
+pub use vm::{java, with_local_frame, Jvm};
+
 #[cfg(feature = "build")]
 mod codegen;
+#[cfg(feature = "build")]
+mod javagen;
+#[cfg(feature = "build")]
+mod javap;
+#[cfg(feature = "cli")]
+pub mod cli;
 
 #[cfg(feature = "build")]
-pub use codegen::generate_bindings_file;
+pub use codegen::{
+    generate_bindings, generate_bindings_file, generate_bindings_file_with, generate_bindings_with_sources,
+    verify_bindings_file, BindingOptions,
+};
+#[cfg(feature = "build")]
+pub use javagen::{compile_java, generate_java_source, NativeClass};
 
 // Runtime re-exports used by generated code.
 pub use jni;
 pub use once_cell;
-pub use lazy_static;
 
 #[cfg(feature = "build")]
 use regex::Regex;
 
 #[cfg(feature = "build")]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 struct MethodBinding {
     path: String,
     name: String,
     signature: String,
     args: Vec<String>,
     return_type: String,
+    /// Return type as javap prints it, including generics
+    /// (e.g. `java.util.Iterator<java.lang.String>`).
+    generic_return: String,
     is_static: bool,
     is_constructor: bool,
+    /// `ACC_VARARGS`: the last argument is a `...` array.
+    is_varargs: bool,
+    /// `ACC_SYNTHETIC`: generated by the compiler, e.g. Kotlin `$default` bridges.
+    is_synthetic: bool,
+    /// Implemented in native code rather than called through JNI.
+    is_native: bool,
 }
 
+/// A record component, from the class's `Record` attribute.
 #[cfg(feature = "build")]
-pub(crate) fn parse_javap_output(class_name: &str, class_path: Option<String>) -> Vec<MethodBinding> {
-    use std::process::Command;
+#[derive(Debug, PartialEq)]
+struct RecordComponent {
+    name: String,
+    descriptor: String,
+}
+
+#[cfg(feature = "build")]
+#[derive(Debug, Default, PartialEq)]
+struct JavaClass {
+    /// The class file or jar javap read the class from; `None` for JDK
+    /// classes.
+    source: Option<std::path::PathBuf>,
+    methods: Vec<MethodBinding>,
+    /// `Some` for records, with the components in declaration order.
+    record: Option<Vec<RecordComponent>>,
+    /// Internal names from the `PermittedSubclasses` attribute of a sealed
+    /// class or interface, e.g. `com/example/Circle`.
+    permitted_subclasses: Vec<String>,
+    /// The `@kotlin.Metadata` annotation, for classes compiled by Kotlin.
+    kotlin: Option<KotlinMetadata>,
+    /// A Kotlin companion object, held in a static field of the class.
+    companion: Option<Box<Companion>>,
+}
+
+#[cfg(feature = "build")]
+#[derive(Debug, PartialEq)]
+struct KotlinMetadata {
+    /// `k`: 1 for a class, 2 for a file facade (`FooKt`), 4 for a
+    /// multi-file facade.
+    kind: u32,
+    /// `d2`: the string table that `d1` indexes into.
+    strings: Vec<String>,
+    /// Names of the properties declared in `d1`.
+    properties: Vec<String>,
+}
+
+#[cfg(feature = "build")]
+#[derive(Debug, PartialEq)]
+struct Companion {
+    /// Name of the static field, `Companion` unless the object is named.
+    field: String,
+    class: JavaClass,
+}
 
-    let mut command = Command::new("javap");
-    command.args(["-s", "-p"]);
+/// Undo the escapes javap uses in string constants.
+#[cfg(feature = "build")]
+fn unescape_javap(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => out.push('\u{8}'),
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('f') => out.push('\u{c}'),
+            Some('r') => out.push('\r'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                out.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+            }
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Property names from the protobuf in `d1`, resolved through `d2`.
+///
+/// `d1` holds one byte per char after a leading `\0` (older compilers
+/// used a 7-bit packing, which isn't supported and yields no properties).
+/// The bytes are a length-delimited `StringTableTypes` message followed
+/// by a `Class` message (`k=1`, properties in field 10) or a `Package`
+/// message (`k=2`, `k=5`, field 4). A property's name is field 2.
+#[cfg(feature = "build")]
+fn kotlin_properties(kind: u32, d1: &str, d2: &[String]) -> Vec<String> {
+    let property_field = match kind {
+        1 => 10,
+        2 | 5 => 4,
+        _ => return Vec::new(),
+    };
+    let Some(data) = d1.strip_prefix('\0') else {
+        return Vec::new();
+    };
+    let Ok(bytes) = data.chars().map(u8::try_from).collect::<Result<Vec<u8>, _>>() else {
+        return Vec::new();
+    };
+
+    let mut input = bytes.as_slice();
+    let message = read_varint(&mut input)
+        .and_then(|len| input.get(usize::try_from(len).ok()?..))
+        .unwrap_or_default();
+    protobuf_fields(message)
+        .filter(|(field, _)| *field == property_field)
+        .filter_map(|(_, value)| match value {
+            ProtobufValue::Bytes(property) => protobuf_fields(property).find_map(|(field, value)| match value {
+                ProtobufValue::Varint(name) if field == 2 => d2.get(usize::try_from(name).ok()?).cloned(),
+                _ => None,
+            }),
+            ProtobufValue::Varint(_) => None,
+        })
+        .collect()
+}
 
-    if let Some(cp) = class_path {
-        command.arg("-classpath").arg(cp);
+#[cfg(feature = "build")]
+enum ProtobufValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+#[cfg(feature = "build")]
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first()?;
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
     }
+    None
+}
+
+/// The top-level fields of a protobuf message, stopping at the first
+/// malformed one. Fixed-width fields are skipped.
+#[cfg(feature = "build")]
+fn protobuf_fields(mut input: &[u8]) -> impl Iterator<Item = (u64, ProtobufValue<'_>)> {
+    std::iter::from_fn(move || loop {
+        let key = read_varint(&mut input)?;
+        let value = match key & 7 {
+            0 => ProtobufValue::Varint(read_varint(&mut input)?),
+            1 | 5 => {
+                let width = if key & 7 == 1 { 8 } else { 4 };
+                input = input.get(width..)?;
+                continue;
+            }
+            2 => {
+                let len = usize::try_from(read_varint(&mut input)?).ok()?;
+                let bytes = input.get(..len)?;
+                input = &input[len..];
+                ProtobufValue::Bytes(bytes)
+            }
+            _ => return None,
+        };
+        return Some((key >> 3, value));
+    })
+}
 
-    command.arg(class_name);
+/// Run `javap` on `class_name` and parse its output, or fail with javap's
+/// message if it couldn't read the class.
+#[cfg(feature = "build")]
+pub(crate) fn parse_javap_output(class_name: &str, class_path: Option<String>) -> std::io::Result<JavaClass> {
+    javap::class(class_name, class_path.as_deref())
+}
 
-    let output = command.output().expect("Failed to execute javap");
-    let output_str = String::from_utf8_lossy(&output.stdout);
+/// Parse `javap -v -p` output for `class_name`, loading the class of a
+/// Kotlin companion object with `companion`.
+#[cfg(feature = "build")]
+fn parse_javap(
+    class_name: &str,
+    output_str: &str,
+    companion: &mut dyn FnMut(&str) -> std::io::Result<JavaClass>,
+) -> std::io::Result<JavaClass> {
 
     let simple_class_name = class_name.split('.').last().unwrap_or(class_name);
 
-    // Group 1 = static modifier
+    // Group 1 = modifiers (static, native, final, ...)
     // Group 2 = "ReturnType MethodName" for regular methods, or just "com.example.ClassName" for constructors.
     // We split group 2 on whitespace and take the last token, then strip any qualifier via '.'.
     let method_regex = Regex::new(
-        r"(?m)^\s*(?:public|private|protected)?\s*(static\s+native|native\s+static|static|native)?\s*([\w$<>\[\].]+(?:\s+[\w$<>]+)?)\s*\(([^)]*)\)\s*(?:throws\s+[\w.,\s]+)?\s*;"
+        r"(?m)^\s*(?:public|private|protected)?\s*((?:(?:static|native|final|synchronized|abstract|strictfp|default)\s+)*)([\w$<>\[\].]+(?:\s+[\w$<>]+)?)\s*\(([^)]*)\)\s*(?:throws\s+[\w.,\s]+)?\s*;"
     ).unwrap();
     let descriptor_regex = Regex::new(r"^\s*descriptor:\s*(.+)$").unwrap();
+    let flags_regex = Regex::new(r"^\s*flags:\s*\(0x[0-9a-f]+\)(.*)$").unwrap();
+
+    let record_component_regex = Regex::new(r"^  \S.*\s([\w$]+);$").unwrap();
+    let static_field_regex = Regex::new(r"^  public static final ([\w.$]+) (\w+);$").unwrap();
+    let string_regex = Regex::new(r#""((?:[^"\\]|\\.)*)""#).unwrap();
 
     let mut bindings = Vec::new();
+    let mut record = None;
+    let mut permitted_subclasses = Vec::new();
+    let mut kotlin = None;
+    let mut companion_field = None;
+    let mut source = None;
     let mut lines = output_str.lines().peekable();
 
     while let Some(line) = lines.next() {
-        if let Some(captures) = method_regex.captures(line) {
-            let is_static = captures.get(1).map_or("", |m| m.as_str()).contains("static");
+        if line.starts_with("Classfile ") {
+            source = javap::classfile_source(line);
+        } else if line == "Record:" {
+            let mut components = Vec::new();
+            let mut name = None;
+            // The attribute runs until the next unindented line.
+            while let Some(next_line) = lines.next_if(|l| l.is_empty() || l.starts_with(' ')) {
+                if let Some(c) = record_component_regex.captures(next_line) {
+                    name = Some(c[1].to_string());
+                } else if let (Some(c), Some(name)) = (descriptor_regex.captures(next_line), name.take()) {
+                    components.push(RecordComponent { name, descriptor: c[1].to_string() });
+                }
+            }
+            record = Some(components);
+        } else if line.trim() == "kotlin.Metadata(" {
+            let mut metadata = KotlinMetadata { kind: 0, strings: Vec::new(), properties: Vec::new() };
+            let mut data = String::new();
+            for next_line in lines.by_ref().map(str::trim).take_while(|l| *l != ")") {
+                if let Some(kind) = next_line.strip_prefix("k=") {
+                    metadata.kind = kind.parse().unwrap_or(0);
+                } else if let Some(d1) = next_line.strip_prefix("d1=") {
+                    data = string_regex.captures_iter(d1).map(|c| unescape_javap(&c[1])).collect();
+                } else if let Some(strings) = next_line.strip_prefix("d2=") {
+                    metadata.strings = string_regex.captures_iter(strings).map(|c| unescape_javap(&c[1])).collect();
+                }
+            }
+            metadata.properties = kotlin_properties(metadata.kind, &data, &metadata.strings);
+            kotlin = Some(metadata);
+        } else if let Some(c) = static_field_regex.captures(line)
+            .filter(|c| c[1] == format!("{}${}", class_name, &c[2]))
+        {
+            companion_field = Some(c[2].to_string());
+        } else if line == "PermittedSubclasses:" {
+            while let Some(next_line) = lines.next_if(|l| l.starts_with(' ')) {
+                permitted_subclasses.push(next_line.trim().to_string());
+            }
+        } else if let Some(captures) = method_regex.captures(line) {
+            let modifiers = captures.get(1).map_or("", |m| m.as_str());
+            let is_static = modifiers.contains("static");
+            let is_native = modifiers.contains("native");
             let combined = captures.get(2).map_or("", |m| m.as_str());
             let last_token = combined.split_whitespace().last().unwrap_or(combined);
             let name = last_token.split('.').last().unwrap_or(last_token).to_string();
+            let generic_return = combined[..combined.len() - last_token.len()].trim().to_string();
             let is_constructor = name == simple_class_name;
 
             while let Some(next_line) = lines.peek() {
@@ -70,14 +306,23 @@ pub(crate) fn parse_javap_output(class_name: &str, class_path: Option<String>) -
                     let args = parse_descriptor_args(&signature);
                     let return_type = parse_descriptor_return(&signature);
 
+                    lines.next();
+                    let flags = lines.peek()
+                        .and_then(|l| flags_regex.captures(l))
+                        .map_or(String::new(), |c| c[1].to_string());
+
                     bindings.push(MethodBinding {
                         path: class_name.replace('.', "/"),
                         name: name.clone(),
                         signature,
                         args,
                         return_type,
+                        generic_return: generic_return.clone(),
                         is_static,
                         is_constructor,
+                        is_varargs: flags.contains("ACC_VARARGS"),
+                        is_synthetic: flags.contains("ACC_SYNTHETIC"),
+                        is_native,
                     });
                     break;
                 }
@@ -86,7 +331,15 @@ pub(crate) fn parse_javap_output(class_name: &str, class_path: Option<String>) -
         }
     }
 
-    bindings
+    let companion = match (&kotlin, companion_field) {
+        (Some(_), Some(field)) => Some(Box::new(Companion {
+            class: companion(&format!("{}${}", class_name, field))?,
+            field,
+        })),
+        _ => None,
+    };
+
+    Ok(JavaClass { source, methods: bindings, record, permitted_subclasses, kotlin, companion })
 }
 
 #[cfg(feature = "build")]
@@ -136,31 +389,248 @@ fn parse_descriptor_return(descriptor: &str) -> String {
     descriptor.split(')').nth(1).unwrap_or("").to_string()
 }
 
+/// The `java.lang.Iterable<T>` a class implements, directly or through its
+/// superclasses and superinterfaces, with type arguments substituted along
+/// the way (e.g. `java.lang.Iterable<java.lang.Object>` for
+/// `javax.management.AttributeList`).
+///
+/// Supertypes are looked up level by level, one javap run per level.
+#[cfg(feature = "build")]
+pub(crate) fn iterable_supertype(class_name: &str, class_path: Option<&str>) -> Option<String> {
+    use std::collections::{HashMap, HashSet};
+    use std::process::Command;
+
+    let mut visited = HashSet::new();
+    let mut level = vec![(class_name.to_string(), Vec::<String>::new())];
+
+    while !level.is_empty() {
+        level.retain(|(name, _)| name != "java.lang.Object" && visited.insert(name.clone()));
+        if level.is_empty() {
+            break;
+        }
+
+        let mut command = Command::new("javap");
+        if let Some(cp) = class_path {
+            command.arg("-classpath").arg(cp);
+        }
+        command.args(level.iter().map(|(name, _)| name));
+        let output = command.output().expect("Failed to execute javap");
+        let declarations: HashMap<String, ClassDeclaration> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_class_declaration)
+            .collect();
+
+        let mut next = Vec::new();
+        for (name, type_args) in &level {
+            let Some(declaration) = declarations.get(name) else { continue };
+            let bindings: HashMap<&str, &str> = declaration.type_params.iter()
+                .map(String::as_str)
+                .zip(type_args.iter().map(String::as_str))
+                .collect();
+
+            for supertype in &declaration.supertypes {
+                let supertype = substitute_type_args(supertype, &bindings);
+                let (super_name, super_args) = split_type_args(&supertype);
+                if super_name == "java.lang.Iterable" {
+                    return Some(supertype);
+                }
+                next.push((super_name.to_string(), super_args));
+            }
+        }
+        level = next;
+    }
+
+    None
+}
+
+/// Type parameters and direct supertypes from a javap class declaration line.
+#[cfg(feature = "build")]
+#[derive(Debug, Default, PartialEq)]
+struct ClassDeclaration {
+    /// Type parameter names, e.g. `["K", "V"]`.
+    type_params: Vec<String>,
+    /// Superclass and interfaces with their type arguments,
+    /// e.g. `java.util.AbstractList<E>`.
+    supertypes: Vec<String>,
+}
+
+/// Parse a declaration such as
+/// `public class com.example.Foo<T extends java.lang.Number> extends com.example.Base<T> implements java.lang.Iterable<T> {`
+/// into the class name and its [`ClassDeclaration`].
+#[cfg(feature = "build")]
+fn parse_class_declaration(line: &str) -> Option<(String, ClassDeclaration)> {
+    let line = line.trim().strip_suffix('{')?.trim_end();
+    let keyword_end = ["class ", "interface "]
+        .iter()
+        .filter_map(|keyword| {
+            line.find(keyword)
+                .filter(|&at| at == 0 || line.as_bytes()[at - 1] == b' ')
+                .map(|at| at + keyword.len())
+        })
+        .min()?;
+    let rest = &line[keyword_end..];
+
+    let name_end = rest.find(['<', ' ']).unwrap_or(rest.len());
+    let name = rest[..name_end].to_string();
+    let mut rest = &rest[name_end..];
+
+    let mut declaration = ClassDeclaration::default();
+    if rest.starts_with('<') {
+        let params_end = matching_angle(rest)?;
+        declaration.type_params = split_top_level(&rest[1..params_end])
+            .iter()
+            .filter_map(|param| param.split_whitespace().next().map(str::to_string))
+            .collect();
+        rest = &rest[params_end + 1..];
+    }
+
+    for clause in [" extends ", " implements "] {
+        let Some(start) = find_top_level(rest, clause) else { continue };
+        let list = &rest[start + clause.len()..];
+        let list = find_top_level(list, " implements ").map_or(list, |end| &list[..end]);
+        declaration.supertypes.extend(split_top_level(list));
+    }
+
+    Some((name, declaration))
+}
+
+/// Index of the `>` closing the `<` that `s` starts with.
+#[cfg(feature = "build")]
+fn matching_angle(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Position of `pattern` in `s` outside any `<...>`.
+#[cfg(feature = "build")]
+fn find_top_level(s: &str, pattern: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 && s[i..].starts_with(pattern) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split a comma-separated type list, ignoring commas inside `<...>`.
+#[cfg(feature = "build")]
+fn split_top_level(list: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(list[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = list[start..].trim();
+    if !last.is_empty() {
+        parts.push(last.to_string());
+    }
+    parts
+}
+
+/// `java.util.Map<K, V>` -> (`java.util.Map`, `["K", "V"]`).
+#[cfg(feature = "build")]
+fn split_type_args(ty: &str) -> (&str, Vec<String>) {
+    match ty.find('<') {
+        Some(start) if ty.ends_with('>') => (&ty[..start], split_top_level(&ty[start + 1..ty.len() - 1])),
+        _ => (ty, Vec::new()),
+    }
+}
+
+/// Replace type variables in `ty` with the types bound to them.
+#[cfg(feature = "build")]
+fn substitute_type_args(ty: &str, bindings: &std::collections::HashMap<&str, &str>) -> String {
+    let mut result = String::new();
+    let mut token = String::new();
+    for c in ty.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() || c == '_' || c == '$' || c == '.' {
+            token.push(c);
+            continue;
+        }
+        result.push_str(bindings.get(token.as_str()).copied().unwrap_or(&token));
+        token.clear();
+        result.push(c);
+    }
+    result.pop();
+    result
+}
+
 #[cfg(all(test, feature = "build"))]
 mod tests {
     use super::*;
 
+    /// Parse a capture from `tests/fixtures/javap` (regenerated by
+    /// `capture.sh` there).
+    pub(crate) fn parse_fixture(class_name: &str) -> JavaClass {
+        let path = format!("{}/tests/fixtures/javap/{}.txt", env!("CARGO_MANIFEST_DIR"), class_name);
+        let output = std::fs::read_to_string(path).unwrap();
+        parse_javap(class_name, &output, &mut |companion| Ok(parse_fixture(companion))).unwrap()
+    }
+
+    fn method<'a>(class: &'a JavaClass, name: &str) -> &'a MethodBinding {
+        class.methods.iter().find(|b| b.name == name).unwrap_or_else(|| panic!("no method `{}`", name))
+    }
+
     #[test]
     fn test_parse_descriptor() {
         assert_eq!(parse_descriptor_args("(II)I"), vec!["I", "I"]);
         assert_eq!(
             parse_descriptor_args("(ILjava/lang/String;[I)V"),
-            vec!["I", "java/lang/String", "[I"]
+            vec!["I", "Ljava/lang/String", "[I"]
         );
         assert_eq!(parse_descriptor_return("(II)I"), "I");
         assert_eq!(
             parse_descriptor_args("(Lcom/example/EnumTest$CountEnum;)I"),
-            vec!["Lcom/example/EnumTest$CountEnum;"]
+            vec!["Lcom/example/EnumTest$CountEnum"]
         );
+        assert_eq!(parse_descriptor_args("([Ljava/lang/String;J)V"), vec!["[Ljava/lang/String;", "J"]);
         assert_eq!(parse_descriptor_return("(Lcom/example/EnumTest$CountEnum;)I"), "I");
     }
 
     #[test]
     fn test_parse_car() {
+        // The example sources aren't compiled in the tree.
+        let classes = std::env::temp_dir().join(format!("auto-jni-car-{}", std::process::id()));
+        let sources = std::fs::read_dir("examples/java/src/com/example").unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "java"));
+        let status = std::process::Command::new("javac")
+            .arg("-d")
+            .arg(&classes)
+            .args(sources)
+            .status()
+            .unwrap();
+        assert!(status.success(), "javac failed");
+
         let bindings = parse_javap_output(
             "com.example.Car",
-            Some("examples/java/src".to_string()),
-        );
+            Some(classes.display().to_string()),
+        ).unwrap().methods;
+        std::fs::remove_dir_all(&classes).unwrap();
         assert!(!bindings.is_empty(), "No bindings parsed");
 
         let ctor = bindings.iter().find(|b| b.is_constructor).expect("No constructor");
@@ -170,4 +640,155 @@ mod tests {
         assert!(bindings.iter().any(|b| b.name == "getMake"));
         assert!(bindings.iter().any(|b| b.name == "displayInfo"));
     }
+
+    #[test]
+    fn test_missing_class() {
+        let dir = std::env::temp_dir().join(format!("auto-jni-javap-missing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let err = parse_javap_output("com.example.Nope", Some(dir.display().to_string())).unwrap_err();
+        assert!(err.to_string().contains("javap couldn't read `com.example.Nope`"), "{}", err);
+        // javap's own message, from its stderr.
+        assert!(err.to_string().contains("class not found"), "{}", err);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_varargs() {
+        let class = parse_fixture("com.fixture.Varargs");
+        assert_eq!(class.methods.len(), 5);
+
+        let ctor = method(&class, "Varargs");
+        assert!(ctor.is_constructor && ctor.is_varargs);
+        assert_eq!(ctor.signature, "([Ljava/lang/String;)V");
+
+        let join = method(&class, "join");
+        assert!(join.is_static && join.is_varargs);
+        assert_eq!(join.args, ["Ljava/lang/String", "[Ljava/lang/String;"]);
+        assert_eq!(join.return_type, "Ljava/lang/String;");
+
+        let sum = method(&class, "sum");
+        assert!(sum.is_varargs && !sum.is_static);
+        assert_eq!(sum.signature, "([I)I");
+        // A plain array parameter isn't varargs.
+        assert!(!method(&class, "take").is_varargs);
+
+        let names = method(&class, "names");
+        assert_eq!(names.return_type, "Ljava/util/List;");
+        assert_eq!(names.generic_return, "java.util.List<java.lang.String>");
+
+        assert!(class.record.is_none() && class.permitted_subclasses.is_empty());
+    }
+
+    #[test]
+    fn test_parse_class_declaration() {
+        let (name, declaration) = parse_class_declaration(
+            "public class com.example.Foo<K extends java.lang.Comparable<K>, V> extends com.example.Base<java.util.Map<K, V>> implements java.lang.Iterable<V>, java.io.Serializable {",
+        )
+        .unwrap();
+        assert_eq!(name, "com.example.Foo");
+        assert_eq!(declaration.type_params, vec!["K", "V"]);
+        assert_eq!(
+            declaration.supertypes,
+            vec!["com.example.Base<java.util.Map<K, V>>", "java.lang.Iterable<V>", "java.io.Serializable"]
+        );
+
+        let (name, declaration) = parse_class_declaration(
+            "public interface java.nio.file.Path extends java.lang.Comparable<java.nio.file.Path>, java.lang.Iterable<java.nio.file.Path>, java.nio.file.Watchable {",
+        )
+        .unwrap();
+        assert_eq!(name, "java.nio.file.Path");
+        assert!(declaration.type_params.is_empty());
+        assert_eq!(declaration.supertypes.len(), 3);
+
+        assert_eq!(parse_class_declaration("  public static {};"), None);
+    }
+
+    #[test]
+    fn test_iterable_supertype() {
+        // Declared directly.
+        assert_eq!(
+            iterable_supertype("java.nio.file.Path", None).as_deref(),
+            Some("java.lang.Iterable<java.nio.file.Path>")
+        );
+        // Inherited, with type arguments substituted through ArrayList<E>.
+        assert_eq!(
+            iterable_supertype("javax.management.AttributeList", None).as_deref(),
+            Some("java.lang.Iterable<java.lang.Object>")
+        );
+        assert_eq!(iterable_supertype("java.lang.String", None), None);
+    }
+
+    #[test]
+    fn test_parse_sealed_records() {
+        let shape = parse_fixture("com.fixture.Shape");
+        assert_eq!(shape.permitted_subclasses, ["com/fixture/Shape$Circle", "com/fixture/Shape$Square"]);
+        assert!(shape.record.is_none());
+
+        let circle = parse_fixture("com.fixture.Shape$Circle");
+        assert!(circle.permitted_subclasses.is_empty());
+        let components = circle.record.as_ref().unwrap();
+        assert_eq!(components.len(), 1);
+        assert_eq!((components[0].name.as_str(), components[0].descriptor.as_str()), ("radius", "D"));
+        assert_eq!(method(&circle, "Shape$Circle").signature, "(D)V");
+
+        let square = parse_fixture("com.fixture.Shape$Square");
+        let components: Vec<(&str, &str)> = square.record.as_ref().unwrap().iter()
+            .map(|c| (c.name.as_str(), c.descriptor.as_str()))
+            .collect();
+        assert_eq!(components, [("side", "D"), ("label", "Ljava/lang/String;")]);
+        assert_eq!(method(&square, "label").return_type, "Ljava/lang/String;");
+    }
+
+    #[test]
+    fn test_parse_kotlin() {
+        let account = parse_fixture("com.fixture.Account");
+        let kotlin = account.kotlin.as_ref().unwrap();
+        assert_eq!(kotlin.kind, 1);
+        assert_eq!(&kotlin.strings[..3], ["Lcom/fixture/Account;", "", "balance"]);
+        assert_eq!(kotlin.properties, ["balance", "isOpen"]);
+        assert!(method(&account, "deposit$default").is_synthetic);
+        assert!(method(&account, "lambda$audit$0").is_synthetic);
+        assert!(method(&account, "open").is_static);
+
+        // The companion object's class is loaded through its static field.
+        let companion = account.companion.as_ref().unwrap();
+        assert_eq!(companion.field, "Companion");
+        assert_eq!(companion.class.kotlin.as_ref().unwrap().properties, ["limit"]);
+        let methods: Vec<(&str, bool)> = companion.class.methods.iter()
+            .map(|b| (b.name.as_str(), b.is_constructor))
+            .collect();
+        assert_eq!(methods, [("Account$Companion", true), ("open", false), ("create", false), ("getLimit", false)]);
+        assert!(companion.class.companion.is_none());
+
+        let facade = parse_fixture("com.fixture.StringUtilsKt");
+        assert_eq!(facade.kotlin.as_ref().unwrap().kind, 2);
+        assert!(facade.kotlin.as_ref().unwrap().properties.is_empty());
+        assert!(facade.companion.is_none());
+        assert!(method(&facade, "shout").is_static && method(&facade, "count").is_static);
+
+        // Only Kotlin classes have companions.
+        assert!(parse_fixture("com.fixture.Varargs").companion.is_none());
+    }
+
+    #[test]
+    fn test_parse_native_synthetic() {
+        let class = parse_fixture("com.fixture.Natives");
+
+        let add = method(&class, "add");
+        assert!(add.is_native && add.is_static && !add.is_synthetic);
+        assert_eq!(add.signature, "(II)I");
+        let describe = method(&class, "describe");
+        assert!(describe.is_native && !describe.is_static);
+        assert_eq!(describe.args, ["J"]);
+        assert!(!method(&class, "counter").is_native);
+
+        // The Comparable bridge and the lambda body are compiler-generated.
+        let compare_to: Vec<(&str, bool)> = class.methods.iter()
+            .filter(|b| b.name == "compareTo")
+            .map(|b| (b.signature.as_str(), b.is_synthetic))
+            .collect();
+        assert_eq!(compare_to, [("(Lcom/fixture/Natives;)I", false), ("(Ljava/lang/Object;)I", true)]);
+        let lambda = method(&class, "lambda$counter$0");
+        assert!(lambda.is_synthetic && lambda.is_static && !lambda.is_native);
+    }
 }
@@ -0,0 +1,166 @@
+//! Support for `native` methods implemented in Rust and registered with
+//! `RegisterNatives` from a generated `JNI_OnLoad`.
+//!
+//! A panic unwinding out of an `extern "system"` function aborts the JVM,
+//! so every Rust function Java calls runs inside [`guard`], which turns
+//! panics and `Err` results into Java exceptions.
+
+use std::any::Any;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::RwLock;
+
+use jni::sys::{jint, JNI_ERR, JNI_VERSION_1_8};
+use jni::{JNIEnv, JavaVM};
+
+use crate::errors::JNIError;
+use crate::vm::Jvm;
+
+/// Body of a generated `JNI_OnLoad`.
+///
+/// Registers `vm` as the process JVM and runs `register`, which returns the
+/// native methods left without a Rust function. If any are missing, or
+/// registration fails, loading the library fails with an
+/// `UnsatisfiedLinkError` saying why, rather than the first call to a
+/// missing method failing later.
+///
+/// # Safety
+///
+/// `vm` must be the pointer the JVM passed to `JNI_OnLoad`.
+pub unsafe fn on_load(
+    vm: *mut jni::sys::JavaVM,
+    register: impl FnOnce(&mut JNIEnv<'static>) -> Result<Vec<String>, JNIError>,
+) -> jint {
+    let Ok(vm) = JavaVM::from_raw(vm) else {
+        return JNI_ERR;
+    };
+    let mut env = Jvm::register(Box::leak(Box::new(vm))).env();
+    let message = match panic::catch_unwind(AssertUnwindSafe(|| register(&mut env))) {
+        Ok(Ok(missing)) if missing.is_empty() => return JNI_VERSION_1_8,
+        Ok(Ok(missing)) => format!("no Rust implementation for native methods: {}", missing.join(", ")),
+        Ok(Err(err)) => format!("failed to register native methods: {}", err),
+        Err(payload) => format!("panic while registering native methods: {}", panic_message(&*payload)),
+    };
+    // A failed `RegisterNatives` leaves its own `NoSuchMethodError` pending.
+    if !env.exception_check().unwrap_or(false) {
+        let _ = env.throw_new("java/lang/UnsatisfiedLinkError", message);
+    }
+    JNI_ERR
+}
+
+static EXCEPTION_CLASS: RwLock<&str> = RwLock::new("java/lang/RuntimeException");
+
+/// Throw `class` (in `java/lang/RuntimeException` form, with a `String`
+/// constructor) instead of `RuntimeException` for Rust errors and panics.
+pub fn set_exception_class(class: &'static str) {
+    *EXCEPTION_CLASS.write().unwrap_or_else(|err| err.into_inner()) = class;
+}
+
+/// Run the body of a function called from Java, returning `default` to
+/// Java with an exception pending if it fails or panics.
+///
+/// An `Err` is thrown as the exception class (see [`set_exception_class`])
+/// with the error as its message, except `JNIError::JavaException`, whose
+/// Java exception is already pending and propagates as it is. A panic is
+/// thrown with the panic message.
+pub fn guard<'local, R>(
+    env: &mut JNIEnv<'local>,
+    default: R,
+    body: impl FnOnce(&mut JNIEnv<'local>) -> Result<R, JNIError>,
+) -> R {
+    match panic::catch_unwind(AssertUnwindSafe(|| body(env))) {
+        Ok(Ok(value)) => value,
+        Ok(Err(err)) => {
+            throw(env, &err);
+            default
+        }
+        Err(payload) => {
+            throw_message(env, &format!("Rust panic: {}", panic_message(&*payload)));
+            default
+        }
+    }
+}
+
+/// Throw `err` from a native method, unless an exception is already pending.
+pub fn throw(env: &mut JNIEnv, err: &JNIError) {
+    throw_message(env, &err.to_string());
+}
+
+fn throw_message(env: &mut JNIEnv, message: &str) {
+    if env.exception_check().unwrap_or(false) {
+        return;
+    }
+    let class = *EXCEPTION_CLASS.read().unwrap_or_else(|err| err.into_inner());
+    if env.throw_new(class, message).is_err() {
+        // The configured class couldn't be thrown; don't lose the message.
+        let _ = env.exception_clear();
+        let _ = env.throw_new("java/lang/RuntimeException", message);
+    }
+}
+
+/// The message a panic was started with.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload.downcast_ref::<String>().map_or("Box<dyn Any>", String::as_str),
+    }
+}
+
+/// A `RegisterNatives` entry.
+pub fn method(name: &str, sig: &str, fn_ptr: *mut c_void) -> jni::NativeMethod {
+    jni::NativeMethod {
+        name: name.into(),
+        sig: sig.into(),
+        fn_ptr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throw() {
+        let mut env = crate::vm::test_jvm().env();
+
+        throw(&mut env, &JNIError::NullPtr);
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env.is_instance_of(&exception, "java/lang/RuntimeException").unwrap());
+        assert_eq!(message(&mut env, &exception), "null pointer");
+    }
+
+    fn message(env: &mut JNIEnv, exception: &jni::objects::JThrowable) -> String {
+        let message = env.call_method(exception, "getMessage", "()Ljava/lang/String;", &[]).unwrap().l().unwrap();
+        env.get_string(&message.into()).unwrap().into()
+    }
+
+    #[test]
+    fn test_guard() {
+        let mut env = crate::vm::test_jvm().env();
+
+        assert_eq!(guard(&mut env, 0, |_| Ok(7)), 7);
+        assert!(!env.exception_check().unwrap());
+
+        assert_eq!(guard(&mut env, -1, |_| Err(JNIError::Exception("bad input".into()))), -1);
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert_eq!(message(&mut env, &exception), "Java exception: bad input");
+
+        let value: i32 = guard(&mut env, -1, |_| panic!("index {} out of range", 3));
+        assert_eq!(value, -1);
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env.is_instance_of(&exception, "java/lang/RuntimeException").unwrap());
+        assert_eq!(message(&mut env, &exception), "Rust panic: index 3 out of range");
+
+        // A pending Java exception is left as it is.
+        guard(&mut env, (), |env| {
+            env.call_static_method("java/lang/Integer", "parseInt", "(Ljava/lang/String;)I", &[(&env.new_string("x")?).into()])?;
+            Ok(())
+        });
+        let exception = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        assert!(env.is_instance_of(&exception, "java/lang/NumberFormatException").unwrap());
+    }
+}
@@ -0,0 +1,351 @@
+//! Wrapper traits, conversions and `java.lang.Object` protocol helpers.
+//!
+//! Generated wrappers implement `Display`, `PartialEq`/`Eq` and `Hash` on
+//! top of the functions in this module.
+
+use jni::objects::{AutoLocal, GlobalRef, JMethodID, JObject, JString, JValue, JValueOwned};
+use jni::signature::{Primitive, ReturnType};
+use jni::sys::jvalue;
+use jni::JNIEnv;
+use once_cell::sync::OnceCell;
+
+use crate::errors::JNIError;
+use crate::{call, call_static, java};
+
+/// Implemented by every generated class wrapper.
+///
+/// Wrappers only hold a `GlobalRef`, which is valid on any thread, so they
+/// are required to be `Send + Sync`.
+pub trait JavaObject: Sized + Send + Sync {
+    /// Internal name of the wrapped class, e.g. `com/example/Car`.
+    const CLASS: &'static str;
+
+    /// Wrap an existing global reference without checking its class.
+    fn from_global(inner: GlobalRef) -> Self;
+
+    fn inner(&self) -> &GlobalRef;
+
+    /// Wrap a local reference by promoting it to a new global reference.
+    fn from_local(obj: &JObject) -> Result<Self, JNIError> {
+        Ok(Self::from_global(java().new_global_ref(obj)?))
+    }
+}
+
+/// Conversion from a Java object to a Rust value, used for elements of
+/// iterators and other values whose type is only known from generics.
+///
+/// `from_java` takes ownership of the local reference and releases it.
+pub trait FromJava: Sized {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError>;
+}
+
+impl<T: JavaObject> FromJava for T {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        let global = env.new_global_ref(&obj)?;
+        env.delete_local_ref(obj)?;
+        Ok(T::from_global(global))
+    }
+}
+
+impl FromJava for AutoLocal<'static, JObject<'static>> {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        Ok(env.auto_local(obj))
+    }
+}
+
+impl FromJava for GlobalRef {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        let global = env.new_global_ref(&obj)?;
+        env.delete_local_ref(obj)?;
+        Ok(global)
+    }
+}
+
+impl FromJava for String {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        if obj.is_null() {
+            return Err(JNIError::NullPtr);
+        }
+        let s = JString::from(obj);
+        // `get_string` creates local references of its own.
+        let string = env.with_local_frame(4, |env| -> Result<String, JNIError> {
+            Ok(env.get_string(&s)?.into())
+        })?;
+        env.delete_local_ref(s)?;
+        Ok(string)
+    }
+}
+
+/// `null` becomes `None`.
+impl<T: FromJava> FromJava for Option<T> {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        if obj.is_null() {
+            return Ok(None);
+        }
+        T::from_java(env, obj).map(Some)
+    }
+}
+
+/// Unboxes `java.lang.Integer` and friends.
+macro_rules! from_java_boxed {
+    ($ty:ty, $class:literal, $method:literal, $sig:literal, $prim:ident, $get:ident) => {
+        impl FromJava for $ty {
+            fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+                if obj.is_null() {
+                    return Err(JNIError::NullPtr);
+                }
+                let value = call!(
+                    env = env,
+                    &obj,
+                    $class,
+                    $method,
+                    $sig,
+                    &[],
+                    ReturnType::Primitive(Primitive::$prim)
+                )
+                .$get()?;
+                env.delete_local_ref(obj)?;
+                Ok(value)
+            }
+        }
+    };
+}
+
+from_java_boxed!(i32, "java/lang/Integer", "intValue", "()I", Int, i);
+from_java_boxed!(i64, "java/lang/Long", "longValue", "()J", Long, j);
+from_java_boxed!(f64, "java/lang/Double", "doubleValue", "()D", Double, d);
+from_java_boxed!(f32, "java/lang/Float", "floatValue", "()F", Float, f);
+from_java_boxed!(bool, "java/lang/Boolean", "booleanValue", "()Z", Boolean, z);
+from_java_boxed!(i8, "java/lang/Byte", "byteValue", "()B", Byte, b);
+from_java_boxed!(i16, "java/lang/Short", "shortValue", "()S", Short, s);
+from_java_boxed!(u16, "java/lang/Character", "charValue", "()C", Char, c);
+
+/// Unboxes `java.lang.Character`; surrogates are an error.
+impl FromJava for char {
+    fn from_java(env: &mut JNIEnv<'static>, obj: JObject<'static>) -> Result<Self, JNIError> {
+        crate::chars::to_char(u16::from_java(env, obj)?)
+    }
+}
+
+/// Conversion from a Rust value to a new local reference to a Java object,
+/// the reverse of [`FromJava`].
+pub trait IntoJava {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError>;
+}
+
+impl<T: JavaObject> IntoJava for &T {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        Ok(env.new_local_ref(self.inner())?)
+    }
+}
+
+impl IntoJava for &GlobalRef {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        Ok(env.new_local_ref(self)?)
+    }
+}
+
+impl IntoJava for &str {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        Ok(env.new_string(self)?.into())
+    }
+}
+
+impl IntoJava for String {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        self.as_str().into_java(env)
+    }
+}
+
+/// `None` becomes `null`.
+impl<T: IntoJava> IntoJava for Option<T> {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        match self {
+            Some(value) => value.into_java(env),
+            None => Ok(JObject::null()),
+        }
+    }
+}
+
+/// Boxes into `java.lang.Integer` and friends via `valueOf`.
+macro_rules! into_java_boxed {
+    ($ty:ty, $class:literal, $sig:literal, $variant:ident) => {
+        impl IntoJava for $ty {
+            fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+                let boxed = call_static!(
+                    env = env,
+                    $class,
+                    "valueOf",
+                    $sig,
+                    &[JValue::$variant(self).as_jni()],
+                    ReturnType::Object
+                );
+                Ok(boxed.l()?)
+            }
+        }
+    };
+}
+
+into_java_boxed!(i32, "java/lang/Integer", "(I)Ljava/lang/Integer;", Int);
+into_java_boxed!(i64, "java/lang/Long", "(J)Ljava/lang/Long;", Long);
+into_java_boxed!(f64, "java/lang/Double", "(D)Ljava/lang/Double;", Double);
+into_java_boxed!(f32, "java/lang/Float", "(F)Ljava/lang/Float;", Float);
+into_java_boxed!(i8, "java/lang/Byte", "(B)Ljava/lang/Byte;", Byte);
+into_java_boxed!(i16, "java/lang/Short", "(S)Ljava/lang/Short;", Short);
+into_java_boxed!(u16, "java/lang/Character", "(C)Ljava/lang/Character;", Char);
+
+impl IntoJava for bool {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        let boxed = call_static!(
+            env = env,
+            "java/lang/Boolean",
+            "valueOf",
+            "(Z)Ljava/lang/Boolean;",
+            &[JValue::Bool(self as u8).as_jni()],
+            ReturnType::Object
+        );
+        Ok(boxed.l()?)
+    }
+}
+
+/// Boxes into `java.lang.Character`; characters outside the BMP are an error.
+impl IntoJava for char {
+    fn into_java(self, env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        crate::chars::to_java_char(self)?.into_java(env)
+    }
+}
+
+/// `obj.toString()`, or `"null"` if it returns null.
+///
+/// If `toString` throws, the exception is cleared and
+/// [`JNIError::JavaException`] is returned.
+pub fn to_string(obj: &JObject) -> Result<String, JNIError> {
+    static TO_STRING: OnceCell<JMethodID> = OnceCell::new();
+    let mut env = java();
+    let result = call_object_method(&mut env, &TO_STRING, obj, "toString", "()Ljava/lang/String;", ReturnType::Object, &[])?;
+    let string = Option::<String>::from_java(&mut env, result.l()?)?;
+    Ok(string.unwrap_or_else(|| "null".to_string()))
+}
+
+/// `a.equals(b)`.
+///
+/// `PartialEq` can't fail, so if `equals` throws, the exception is cleared
+/// and this falls back to reference identity, like `Object.equals` does.
+pub fn equals(a: &JObject, b: &JObject) -> bool {
+    static EQUALS: OnceCell<JMethodID> = OnceCell::new();
+    let mut env = java();
+    let args = [JValue::Object(b).as_jni()];
+    match call_object_method(&mut env, &EQUALS, a, "equals", "(Ljava/lang/Object;)Z", ReturnType::Primitive(Primitive::Boolean), &args) {
+        Ok(result) => result.z().unwrap(),
+        Err(_) => env.is_same_object(a, b).unwrap(),
+    }
+}
+
+/// `obj.hashCode()`.
+///
+/// If `hashCode` throws, the exception is cleared and the identity hash code
+/// is used instead, consistent with the fallback in [`equals`].
+pub fn hash_code(obj: &JObject) -> i32 {
+    static HASH_CODE: OnceCell<JMethodID> = OnceCell::new();
+    let mut env = java();
+    match call_object_method(&mut env, &HASH_CODE, obj, "hashCode", "()I", ReturnType::Primitive(Primitive::Int), &[]) {
+        Ok(result) => result.i().unwrap(),
+        Err(_) => identity_hash_code(obj),
+    }
+}
+
+/// Call a `java.lang.Object` method, clearing any exception it throws.
+fn call_object_method<'local>(
+    env: &mut JNIEnv<'local>,
+    id: &OnceCell<JMethodID>,
+    obj: &JObject,
+    name: &str,
+    sig: &str,
+    ret: ReturnType,
+    args: &[jvalue],
+) -> Result<JValueOwned<'local>, JNIError> {
+    let id = id.get_or_try_init(|| env.get_method_id("java/lang/Object", name, sig))?;
+    let result = unsafe { env.call_method_unchecked(obj, id, ret, args) };
+    if env.exception_check()? {
+        env.exception_clear()?;
+        return Err(JNIError::JavaException);
+    }
+    Ok(result?)
+}
+
+/// `System.identityHashCode(obj)`.
+pub fn identity_hash_code(obj: &JObject) -> i32 {
+    call_static!(
+        "java/lang/System",
+        "identityHashCode",
+        "(Ljava/lang/Object;)I",
+        &[JValue::Object(obj).as_jni()],
+        ReturnType::Primitive(Primitive::Int)
+    )
+    .i()
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Object {
+        inner: GlobalRef,
+    }
+
+    impl JavaObject for Object {
+        const CLASS: &'static str = "java/lang/Object";
+
+        fn from_global(inner: GlobalRef) -> Self {
+            Self { inner }
+        }
+
+        fn inner(&self) -> &GlobalRef {
+            &self.inner
+        }
+    }
+
+    #[test]
+    fn test_from_local() {
+        let env = crate::vm::test_jvm().env();
+        let local = env.new_string("hello").unwrap();
+        let obj = Object::from_local(&local).unwrap();
+
+        assert!(env.is_same_object(&local, obj.inner()).unwrap());
+        assert_eq!(identity_hash_code(&local), identity_hash_code(obj.inner().as_obj()));
+    }
+
+    #[test]
+    fn test_object_protocol() {
+        let env = crate::vm::test_jvm().env();
+        let a = env.new_string("hello").unwrap();
+        let b = env.new_string("hello").unwrap();
+        let c = env.new_string("world").unwrap();
+
+        assert_eq!(to_string(&a).unwrap(), "hello");
+        assert!(equals(&a, &b));
+        assert!(!equals(&a, &c));
+        assert_eq!(hash_code(&a), hash_code(&b));
+        let expected = "hello".bytes().fold(0i32, |h, c| h.wrapping_mul(31).wrapping_add(c as i32));
+        assert_eq!(hash_code(&a), expected);
+    }
+
+    #[test]
+    fn test_object_protocol_exceptions() {
+        let mut env = crate::vm::test_jvm().env();
+        // Lists that contain themselves overflow the stack in hashCode and
+        // in equals against another such list.
+        let a = env.new_object("java/util/ArrayList", "()V", &[]).unwrap();
+        let b = env.new_object("java/util/ArrayList", "()V", &[]).unwrap();
+        for list in [&a, &b] {
+            env.call_method(list, "add", "(Ljava/lang/Object;)Z", &[list.into()]).unwrap();
+        }
+
+        assert_eq!(hash_code(&a), identity_hash_code(&a));
+        assert!(!equals(&a, &b));
+        assert!(equals(&a, &a));
+        assert!(!env.exception_check().unwrap());
+        assert_eq!(to_string(&a).unwrap(), "[(this Collection)]");
+    }
+}
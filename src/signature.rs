@@ -0,0 +1,141 @@
+//! Compile-time checks of JNI method signatures.
+//!
+//! `call!`, `call_static!` and `create!` run these in a `const` when the
+//! signature is a string literal, so a malformed signature, or one that
+//! disagrees with the arguments or `ReturnType` passed with it, is a
+//! compile error instead of a panic on the first call. Argument counts are
+//! checked when the arguments are written as `&[...]`, and the return type
+//! unless the `ReturnType` is a variable.
+
+use jni::signature::{Primitive, ReturnType};
+
+/// Check a method signature, and its argument count and return type if
+/// known.
+pub const fn check_method(sig: &str, args: Option<usize>, ret: Option<ReturnType>) {
+    let returns = check(sig, args);
+    let matches = match ret {
+        None => true,
+        Some(ReturnType::Primitive(primitive)) => returns == descriptor(primitive),
+        // Arrays are objects to JNI.
+        Some(ReturnType::Object | ReturnType::Array) => returns == b'L' || returns == b'[',
+    };
+    if !matches {
+        panic!("JNI signature: the return type doesn't match the `ReturnType` passed");
+    }
+}
+
+/// Check a constructor signature and its argument count if known.
+pub const fn check_constructor(sig: &str, args: Option<usize>) {
+    if check(sig, args) != b'V' {
+        panic!("JNI signature: constructors return `V`");
+    }
+}
+
+/// Parse `sig`, returning the first character of its return type.
+const fn check(sig: &str, args: Option<usize>) -> u8 {
+    let bytes = sig.as_bytes();
+    if bytes.is_empty() || bytes[0] != b'(' {
+        panic!("JNI signature: must start with `(`");
+    }
+    let mut i = 1;
+    let mut count = 0;
+    while i < bytes.len() && bytes[i] != b')' {
+        i = field_type(bytes, i);
+        count += 1;
+    }
+    if i + 1 >= bytes.len() {
+        panic!("JNI signature: missing `)` or return type");
+    }
+    let returns = bytes[i + 1];
+    let end = if returns == b'V' { i + 2 } else { field_type(bytes, i + 1) };
+    if end != bytes.len() {
+        panic!("JNI signature: unexpected characters after the return type");
+    }
+    if let Some(args) = args {
+        if args != count {
+            panic!("JNI signature: the number of arguments doesn't match the signature");
+        }
+    }
+    returns
+}
+
+/// The index after the field type starting at `i`.
+const fn field_type(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i] == b'[' {
+        i += 1;
+    }
+    if i >= bytes.len() {
+        panic!("JNI signature: array without an element type");
+    }
+    match bytes[i] {
+        b'Z' | b'B' | b'C' | b'S' | b'I' | b'J' | b'F' | b'D' => i + 1,
+        b'L' => {
+            let start = i + 1;
+            i = start;
+            while i < bytes.len() && bytes[i] != b';' {
+                match bytes[i] {
+                    b'.' => panic!("JNI signature: class names are separated with `/`, not `.`"),
+                    b'(' | b')' | b'[' => panic!("JNI signature: class name not terminated with `;`"),
+                    _ => i += 1,
+                }
+            }
+            if i >= bytes.len() {
+                panic!("JNI signature: class name not terminated with `;`");
+            }
+            if i == start {
+                panic!("JNI signature: empty class name");
+            }
+            i + 1
+        }
+        _ => panic!("JNI signature: unknown type; expected one of `ZBCSIJFD`, `L...;` or `[`"),
+    }
+}
+
+const fn descriptor(primitive: Primitive) -> u8 {
+    match primitive {
+        Primitive::Boolean => b'Z',
+        Primitive::Byte => b'B',
+        Primitive::Char => b'C',
+        Primitive::Short => b'S',
+        Primitive::Int => b'I',
+        Primitive::Long => b'J',
+        Primitive::Float => b'F',
+        Primitive::Double => b'D',
+        Primitive::Void => b'V',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+
+    fn error(check: impl FnOnce() + panic::UnwindSafe) -> String {
+        let payload = panic::catch_unwind(check).unwrap_err();
+        payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_signatures() {
+        check_method("(ILjava/lang/String;[[D)V", Some(3), Some(ReturnType::Primitive(Primitive::Void)));
+        check_method("()[Ljava/lang/Object;", Some(0), Some(ReturnType::Object));
+        check_method("(Lcom/example/Car$CarType;)Ljava/util/List;", None, None);
+        check_constructor("(Ljava/lang/String;)V", Some(1));
+
+        assert!(error(|| check_method("(Ljava/lang/String)V", None, None))
+            .contains("not terminated with `;`"));
+        assert!(error(|| check_method("(Ljava.lang.String;)V", None, None))
+            .contains("separated with `/`"));
+        assert!(error(|| check_method("(II)I", Some(1), None))
+            .contains("number of arguments"));
+        assert!(error(|| check_method("(II)I", Some(2), Some(ReturnType::Object))).contains("`ReturnType`"));
+        assert!(error(|| check_method("(I)VV", None, None))
+            .contains("after the return type"));
+        assert!(error(|| check_method("(Q)V", None, None))
+            .contains("unknown type"));
+        assert!(error(|| check_constructor("()I", Some(0))).contains("constructors return `V`"));
+    }
+}
@@ -0,0 +1,136 @@
+//! `java.util.stream.Stream` interop.
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use jni::objects::{AutoLocal, GlobalRef, JObject, JValue};
+use jni::signature::{Primitive, ReturnType};
+
+use crate::iter::JIterator;
+use crate::object::{FromJava, IntoJava};
+use crate::{call, call_static, java};
+
+/// A Java `Stream` whose elements convert to `T`.
+///
+/// Iterating pulls elements lazily through the stream's `iterator()`, so the
+/// pipeline only runs as far as the Rust side consumes it. Like any Java
+/// stream it can be consumed once. Derefs to `JObject` so it can be passed
+/// to bound methods taking a `Stream`.
+pub struct JStream<T = AutoLocal<'static, JObject<'static>>> {
+    inner: GlobalRef,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> JStream<T> {
+    /// Wrap a `java.util.stream.Stream`.
+    pub fn new(stream: &JObject) -> Self {
+        Self {
+            inner: java().new_global_ref(stream).unwrap(),
+            _item: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &GlobalRef {
+        &self.inner
+    }
+}
+
+impl<T> Deref for JStream<T> {
+    type Target = JObject<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_obj()
+    }
+}
+
+impl<T: FromJava> IntoIterator for JStream<T> {
+    type Item = T;
+    type IntoIter = JIterator<T>;
+
+    fn into_iter(self) -> JIterator<T> {
+        let mut env = java();
+        let iterator = call!(
+            env = &mut env,
+            self.inner.as_obj(),
+            "java/util/stream/BaseStream",
+            "iterator",
+            "()Ljava/util/Iterator;",
+            &[],
+            ReturnType::Object
+        );
+        let iterator = env.auto_local(iterator.l().unwrap());
+        JIterator::new(&iterator)
+    }
+}
+
+/// Build a Java `Stream` from a Rust iterator.
+///
+/// The elements are converted and added to a `Stream.Builder` up front;
+/// the resulting stream is then as lazy as any other Java stream.
+impl<T: IntoJava> FromIterator<T> for JStream<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut env = java();
+        let builder = call_static!(
+            env = &mut env,
+            "java/util/stream/Stream",
+            "builder",
+            "()Ljava/util/stream/Stream$Builder;",
+            &[],
+            ReturnType::Object
+        );
+        let builder = env.auto_local(builder.l().unwrap());
+        for item in iter {
+            let item = item.into_java(&mut env).unwrap();
+            let item = env.auto_local(item);
+            call!(
+                env = &mut env,
+                &builder,
+                "java/util/stream/Stream$Builder",
+                "accept",
+                "(Ljava/lang/Object;)V",
+                &[JValue::Object(&item).as_jni()],
+                ReturnType::Primitive(Primitive::Void)
+            );
+        }
+        let stream = call!(
+            env = &mut env,
+            &builder,
+            "java/util/stream/Stream$Builder",
+            "build",
+            "()Ljava/util/stream/Stream;",
+            &[],
+            ReturnType::Object
+        );
+        let stream = env.auto_local(stream.l().unwrap());
+        Self::new(&stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        crate::vm::test_jvm();
+
+        let stream: JStream<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let items: Vec<String> = stream.into_iter().collect();
+        assert_eq!(items, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_lazy_pipeline() {
+        let mut env = crate::vm::test_jvm().env();
+
+        // The Java pipeline only hands over the five elements left after limit().
+        let stream: JStream<i32> = (0..1000).collect();
+        let limited = env
+            .call_method(&*stream, "limit", "(J)Ljava/util/stream/Stream;", &[JValue::Long(5)])
+            .unwrap()
+            .l()
+            .unwrap();
+        let items: Vec<i32> = JStream::new(&limited).into_iter().collect();
+        assert_eq!(items, [0, 1, 2, 3, 4]);
+    }
+}
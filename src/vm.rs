@@ -0,0 +1,292 @@
+//! Process-wide JVM management.
+//!
+//! JNI allows only one JVM per process. This module owns it: the JVM is
+//! either started explicitly with [`init`], started lazily with the defaults
+//! baked into generated bindings via [`get_or_init`], or adopted from a JVM
+//! that is already running in the process (see [`adopt`]).
+
+use std::path::PathBuf;
+
+use jni::objects::JObject;
+use jni::sys::{jint, jsize, JNI_OK};
+use jni::{InitArgs, InitArgsBuilder, JNIEnv, JNIVersion, JavaVM};
+use once_cell::sync::OnceCell;
+
+use crate::errors::JNIError;
+
+static JVM: OnceCell<Jvm> = OnceCell::new();
+
+/// Handle to the process-wide `JavaVM` used by the `call!`, `call_static!`
+/// and `create!` macros when no explicit `JNIEnv` is passed.
+#[derive(Clone, Copy, Debug)]
+pub struct Jvm {
+    vm: &'static JavaVM,
+}
+
+impl Jvm {
+    /// Register `vm` as the process JVM, returning the registered handle.
+    ///
+    /// The first registration wins; later calls return the existing handle.
+    pub fn register(vm: &'static JavaVM) -> Jvm {
+        *JVM.get_or_init(|| Jvm { vm })
+    }
+
+    /// The registered JVM, if any.
+    pub fn get() -> Option<Jvm> {
+        JVM.get().copied()
+    }
+
+    pub fn vm(&self) -> &'static JavaVM {
+        self.vm
+    }
+
+    /// Attach the current thread (permanently) and return its `JNIEnv`.
+    pub fn env(&self) -> JNIEnv<'static> {
+        self.vm.attach_current_thread_permanently().unwrap()
+    }
+}
+
+/// `JNIEnv` for the current thread from the registered [`Jvm`].
+///
+/// Panics if no JVM has been registered yet.
+pub fn java() -> JNIEnv<'static> {
+    Jvm::get()
+        .expect("no JVM registered with auto_jni; call vm::init or Jvm::register first")
+        .env()
+}
+
+/// Run `f` inside a new JNI local reference frame on the current thread.
+///
+/// Every local reference created while `f` runs, including objects returned
+/// by generated methods, is released when it returns. Wrap loops that call
+/// into Java in this so they don't fill up the local reference table.
+///
+/// Local references are `!Send`, so the `Send` bound keeps them out of the
+/// return value: return owned values, wrappers or `GlobalRef`s.
+///
+/// ```compile_fail
+/// let leaked = unsafe {
+///     auto_jni::with_local_frame(16, |_| {
+///         let mut env = auto_jni::java();
+///         let s = env.new_string("popped with the frame").unwrap();
+///         env.auto_local(s)
+///     })
+/// };
+/// ```
+///
+/// # Safety
+///
+/// No local reference created inside `f` may be used after it returns. The
+/// return value is checked, but objects returned by generated methods are
+/// `AutoLocal<'static, _>` and can still be stored through state the closure
+/// captures (say, pushed onto an outer `Vec`); using one of those after the
+/// frame is popped is undefined behaviour.
+pub unsafe fn with_local_frame<T: Send>(
+    capacity: i32,
+    f: impl for<'local> FnOnce(&mut JNIEnv<'local>) -> T,
+) -> Result<T, JNIError> {
+    struct PopFrame(JNIEnv<'static>);
+
+    impl Drop for PopFrame {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = self.0.pop_local_frame(&JObject::null());
+            }
+        }
+    }
+
+    let env = java();
+    env.push_local_frame(capacity)?;
+    let mut frame = PopFrame(env);
+    Ok(f(&mut frame.0))
+}
+
+/// Start the process JVM with the given options (e.g. `-Djava.class.path=...`).
+///
+/// Fails with [`JNIError::AlreadyInitialized`] if a JVM is already running,
+/// since its options can no longer be changed.
+pub fn init(options: &[&str]) -> Result<Jvm, JNIError> {
+    init_with(init_args(options)?)
+}
+
+/// Start the process JVM with fully custom `InitArgs`.
+pub fn init_with(args: InitArgs) -> Result<Jvm, JNIError> {
+    // Decide and create under the cell's lock so a concurrent `get_or_init`
+    // cannot start a second JVM in between.
+    let mut started = false;
+    let jvm = JVM.get_or_try_init(|| {
+        if let Some(vm) = created_vm()? {
+            return Ok(Jvm { vm: Box::leak(Box::new(vm)) });
+        }
+        let vm = JavaVM::new(args)?;
+        started = true;
+        Ok::<_, JNIError>(Jvm { vm: Box::leak(Box::new(vm)) })
+    })?;
+    if !started {
+        return Err(JNIError::AlreadyInitialized);
+    }
+    Ok(*jvm)
+}
+
+/// The process JVM, starting it with `default_options` if nothing is running.
+///
+/// This is what generated bindings call; an explicit [`init`] beforehand
+/// takes precedence over the defaults.
+pub fn get_or_init(default_options: &[&str]) -> Result<Jvm, JNIError> {
+    JVM.get_or_try_init(|| {
+        if let Some(vm) = created_vm()? {
+            return Ok(Jvm { vm: Box::leak(Box::new(vm)) });
+        }
+        let vm = JavaVM::new(init_args(default_options)?)?;
+        Ok(Jvm { vm: Box::leak(Box::new(vm)) })
+    })
+    .copied()
+}
+
+/// Adopt a JVM that was started elsewhere in the process (by another crate,
+/// or by the `java` launcher when running as a native library).
+///
+/// Returns `Ok(None)` if no JVM is running.
+pub fn adopt() -> Result<Option<Jvm>, JNIError> {
+    if let Some(jvm) = Jvm::get() {
+        return Ok(Some(jvm));
+    }
+    Ok(created_vm()?.map(|vm| Jvm::register(Box::leak(Box::new(vm)))))
+}
+
+fn init_args(options: &[&str]) -> Result<InitArgs<'static>, JNIError> {
+    let mut builder = InitArgsBuilder::new().version(JNIVersion::V8);
+    for opt in options {
+        builder = builder.option(opt.to_string());
+    }
+    Ok(builder.build()?)
+}
+
+type GetCreatedJavaVMs =
+    unsafe extern "system" fn(*mut *mut jni::sys::JavaVM, jsize, *mut jsize) -> jint;
+
+/// Look up an already running JVM via `JNI_GetCreatedJavaVMs`.
+///
+/// The libjvm already loaded into the process is asked first, so a host JVM
+/// from any JDK is found even when `JAVA_HOME` is unset or points elsewhere.
+/// Only if none is visible is the libjvm found by `java_locator` loaded.
+fn created_vm() -> Result<Option<JavaVM>, JNIError> {
+    let get_created = match loaded_get_created() {
+        Some(get_created) => get_created,
+        None => located_get_created()?,
+    };
+
+    let mut vm: *mut jni::sys::JavaVM = std::ptr::null_mut();
+    let mut count: jsize = 0;
+    unsafe {
+        if get_created(&mut vm, 1, &mut count) != JNI_OK {
+            return Err(JNIError::Unknown);
+        }
+        if count == 0 {
+            return Ok(None);
+        }
+        Ok(Some(JavaVM::from_raw(vm)?))
+    }
+}
+
+/// `JNI_GetCreatedJavaVMs` from a libjvm that is already loaded, e.g. by the
+/// `java` launcher when running as a native library.
+#[cfg(unix)]
+fn loaded_get_created() -> Option<GetCreatedJavaVMs> {
+    let this = libloading::os::unix::Library::this();
+    let get_created = unsafe { this.get::<GetCreatedJavaVMs>(b"JNI_GetCreatedJavaVMs\0") };
+    get_created.ok().map(|symbol| *symbol)
+}
+
+#[cfg(windows)]
+fn loaded_get_created() -> Option<GetCreatedJavaVMs> {
+    // The library stays loaded for as long as the JVM in it is alive.
+    let jvm = libloading::os::windows::Library::open_already_loaded("jvm.dll").ok()?;
+    let get_created = unsafe { jvm.get::<GetCreatedJavaVMs>(b"JNI_GetCreatedJavaVMs\0") };
+    get_created.ok().map(|symbol| *symbol)
+}
+
+/// `JNI_GetCreatedJavaVMs` from the libjvm `java_locator` finds, loading it.
+fn located_get_created() -> Result<GetCreatedJavaVMs, JNIError> {
+    let libjvm_path: PathBuf = [
+        java_locator::locate_jvm_dyn_library()
+            .map_err(|err| JNIError::StartFailed(err.to_string()))?
+            .as_str(),
+        java_locator::get_jvm_dyn_lib_file_name(),
+    ]
+    .iter()
+    .collect();
+
+    unsafe {
+        let libjvm = libloading::Library::new(&libjvm_path)
+            .map_err(|err| JNIError::StartFailed(err.to_string()))?;
+        let get_created = *libjvm
+            .get::<GetCreatedJavaVMs>(b"JNI_GetCreatedJavaVMs\0")
+            .map_err(|err| JNIError::StartFailed(err.to_string()))?;
+        // A running JVM may live in this library; never unload it.
+        std::mem::forget(libjvm);
+        Ok(get_created)
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_jvm() -> Jvm {
+    get_or_init(&["-Xcheck:jni"]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_jvm() {
+        let jvm = test_jvm();
+        assert!(matches!(init(&[]), Err(JNIError::AlreadyInitialized)));
+
+        let adopted = adopt().unwrap().expect("no JVM adopted");
+        assert_eq!(
+            adopted.vm().get_java_vm_pointer(),
+            jvm.vm().get_java_vm_pointer()
+        );
+        let created = created_vm().unwrap().expect("JNI_GetCreatedJavaVMs found nothing");
+        assert_eq!(created.get_java_vm_pointer(), jvm.vm().get_java_vm_pointer());
+    }
+
+    #[test]
+    fn test_with_local_frame() {
+        test_jvm();
+
+        for i in 0..1000 {
+            let s = unsafe {
+                with_local_frame(4, |env| {
+                    let s = env.new_string(i.to_string()).unwrap();
+                    let s: String = env.get_string(&s).unwrap().into();
+                    s
+                })
+            }
+            .unwrap();
+            assert_eq!(s, i.to_string());
+        }
+
+        // An object only referenced by a local created in the frame can be
+        // collected once the frame is gone.
+        let weak = unsafe {
+            with_local_frame(4, |env| {
+                let object = env.new_object("java/lang/Object", "()V", &[]).unwrap();
+                let weak = env.new_object("java/lang/ref/WeakReference", "(Ljava/lang/Object;)V", &[(&object).into()]).unwrap();
+                env.new_global_ref(weak).unwrap()
+            })
+        }
+        .unwrap();
+        let mut env = java();
+        let collected = (0..50).any(|_| {
+            env.call_static_method("java/lang/System", "gc", "()V", &[]).unwrap();
+            let referent = env.call_method(&weak, "get", "()Ljava/lang/Object;", &[]).unwrap().l().unwrap();
+            let collected = referent.is_null();
+            env.delete_local_ref(referent).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            collected
+        });
+        assert!(collected, "the frame's local reference was not released");
+    }
+}
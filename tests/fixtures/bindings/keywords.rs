@@ -0,0 +1,253 @@
+use auto_jni::jni::objects::{AutoLocal, JObject, GlobalRef};
+use auto_jni::jni::objects::{JValue, JObjectArray};
+use auto_jni::jni::signature::{Primitive, ReturnType};
+use auto_jni::jni::JNIEnv;
+use auto_jni::errors::JNIError;
+use auto_jni::{call, call_static, create, vm, JavaObject};
+
+/// JVM options used if nothing started the JVM before the first call.
+pub const JVM_OPTIONS: &[&str] = &[
+    "-Djava.class.path=target/auto-jni-fixtures",
+];
+
+pub fn java() -> JNIEnv<'static> {
+    vm::get_or_init(JVM_OPTIONS).unwrap().env()
+}
+
+pub struct com_fixture_Keywords {
+    inner: GlobalRef,
+}
+
+impl JavaObject for com_fixture_Keywords {
+    const CLASS: &'static str = "com/fixture/Keywords";
+    fn from_global(inner: GlobalRef) -> Self {
+        Self { inner }
+    }
+    fn inner(&self) -> &GlobalRef {
+        &self.inner
+    }
+}
+
+impl Clone for com_fixture_Keywords {
+    fn clone(&self) -> Self {
+        Self { inner: java().new_global_ref(&self.inner).unwrap() }
+    }
+}
+
+impl std::fmt::Debug for com_fixture_Keywords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "com.fixture.Keywords@{:x}", auto_jni::object::identity_hash_code(self.inner.as_obj()))
+    }
+}
+
+impl std::fmt::Display for com_fixture_Keywords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&auto_jni::object::to_string(self.inner.as_obj()).map_err(|_| std::fmt::Error)?)
+    }
+}
+
+impl PartialEq for com_fixture_Keywords {
+    fn eq(&self, other: &Self) -> bool {
+        auto_jni::object::equals(self.inner.as_obj(), other.inner.as_obj())
+    }
+}
+
+impl Eq for com_fixture_Keywords {}
+
+impl std::hash::Hash for com_fixture_Keywords {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(&auto_jni::object::hash_code(self.inner.as_obj()), state)
+    }
+}
+
+impl<'a> com_fixture_Keywords {
+    pub fn from_global(inner: GlobalRef) -> Self {
+        Self { inner }
+    }
+    pub fn from_local(obj: &JObject) -> Result<Self, JNIError> {
+        <Self as JavaObject>::from_local(obj)
+    }
+    /// Copy the record's components into a [`com_fixture_Keywords_Record`].
+    pub fn to_record(&self) -> Result<com_fixture_Keywords_Record, JNIError> {
+        let mut env = java();
+        let obj = env.new_local_ref(&self.inner)?;
+        auto_jni::FromJava::from_java(&mut env, obj)
+    }
+    pub fn new(arg_0: i32, arg_1: &JObject, arg_2: i64, arg_3: bool) -> Result<Self, JNIError> {
+        Ok(Self {
+            inner: create!(env = &mut java(), "com/fixture/Keywords", "(ILjava/lang/String;JZ)V", &[JValue::Int(arg_0).as_jni(), JValue::Object(arg_1).as_jni(), JValue::Long(arg_2).as_jni(), JValue::Bool(arg_3 as u8).as_jni()])
+        })
+    }
+    pub fn toString(&'a self) -> Result<AutoLocal<'static, JObject<'static>>, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "toString",
+            "()Ljava/lang/String;",
+            &[],
+            ReturnType::Object
+        );
+        Ok(java().auto_local(result.l().unwrap()))
+    }
+    pub fn hashCode(&'a self) -> Result<i32, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "hashCode",
+            "()I",
+            &[],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        Ok(result.i().unwrap())
+    }
+    pub fn equals(&'a self, arg_0: &JObject) -> Result<bool, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "equals",
+            "(Ljava/lang/Object;)Z",
+            &[JValue::Object(arg_0).as_jni()],
+            ReturnType::Primitive(Primitive::Boolean)
+        );
+        Ok(result.z().unwrap())
+    }
+    pub fn r#type(&'a self) -> Result<i32, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "type",
+            "()I",
+            &[],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        Ok(result.i().unwrap())
+    }
+    pub fn env(&'a self) -> Result<AutoLocal<'static, JObject<'static>>, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "env",
+            "()Ljava/lang/String;",
+            &[],
+            ReturnType::Object
+        );
+        Ok(java().auto_local(result.l().unwrap()))
+    }
+    pub fn obj(&'a self) -> Result<i64, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "obj",
+            "()J",
+            &[],
+            ReturnType::Primitive(Primitive::Long)
+        );
+        Ok(result.j().unwrap())
+    }
+    pub fn self_(&'a self) -> Result<bool, JNIError> {
+        let result = call!(
+            env = &mut java(),
+            self.inner.as_obj(),
+            "com/fixture/Keywords",
+            "self",
+            "()Z",
+            &[],
+            ReturnType::Primitive(Primitive::Boolean)
+        );
+        Ok(result.z().unwrap())
+    }
+    pub fn inner(&self) -> &GlobalRef {
+        &self.inner
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct com_fixture_Keywords_Record {
+    pub r#type: i32,
+    pub env: String,
+    pub obj: i64,
+    pub self_: bool,
+}
+
+impl com_fixture_Keywords_Record {
+    /// A new `com.fixture.Keywords` with these components.
+    pub fn to_java(&self) -> Result<com_fixture_Keywords, JNIError> {
+        let mut env = java();
+        let obj = auto_jni::IntoJava::into_java(self, &mut env)?;
+        <com_fixture_Keywords as JavaObject>::from_local(&env.auto_local(obj))
+    }
+}
+
+impl auto_jni::FromJava for com_fixture_Keywords_Record {
+    fn from_java(__env: &mut JNIEnv<'static>, __obj: JObject<'static>) -> Result<Self, JNIError> {
+        if __obj.is_null() {
+            return Err(JNIError::NullPtr);
+        }
+        let r#type = call!(
+            env = __env,
+            &__obj,
+            "com/fixture/Keywords",
+            "type",
+            "()I",
+            &[],
+            ReturnType::Primitive(Primitive::Int)
+        );
+        let r#type = r#type.i()?;
+        let env = call!(
+            env = __env,
+            &__obj,
+            "com/fixture/Keywords",
+            "env",
+            "()Ljava/lang/String;",
+            &[],
+            ReturnType::Object
+        );
+        let env = <String as auto_jni::FromJava>::from_java(__env, env.l()?)?;
+        let obj = call!(
+            env = __env,
+            &__obj,
+            "com/fixture/Keywords",
+            "obj",
+            "()J",
+            &[],
+            ReturnType::Primitive(Primitive::Long)
+        );
+        let obj = obj.j()?;
+        let self_ = call!(
+            env = __env,
+            &__obj,
+            "com/fixture/Keywords",
+            "self",
+            "()Z",
+            &[],
+            ReturnType::Primitive(Primitive::Boolean)
+        );
+        let self_ = self_.z()?;
+        __env.delete_local_ref(__obj)?;
+        Ok(Self { r#type, env, obj, self_ })
+    }
+}
+
+impl auto_jni::IntoJava for &com_fixture_Keywords_Record {
+    fn into_java(self, __env: &mut JNIEnv<'static>) -> Result<JObject<'static>, JNIError> {
+        let r#type = self.r#type;
+        let env = auto_jni::IntoJava::into_java(self.env.as_str(), __env)?;
+        let env = __env.auto_local(env);
+        let obj = self.obj;
+        let self_ = self.self_;
+        let __obj = create!(env = __env, "com/fixture/Keywords", "(ILjava/lang/String;JZ)V", &[
+            JValue::Int(r#type).as_jni(),
+            JValue::Object(&env).as_jni(),
+            JValue::Long(obj).as_jni(),
+            JValue::Bool(self_ as u8).as_jni(),
+        ]);
+        Ok(__env.new_local_ref(&__obj)?)
+    }
+}
+
@@ -0,0 +1,16 @@
+#!/bin/sh
+# Regenerate the `javap -v -p` captures in javap/ from the sources in java/.
+# The parser tests read the captures, so they run without a JDK.
+# javac can't emit ACC_SYNTHETIC, so Kotlin's `$default` bridges get it here.
+set -e
+cd "$(dirname "$0")"
+classes=$(mktemp -d)
+javac -d "$classes" $(find java -name '*.java')
+for file in $(cd "$classes" && find com -name '*.class' | sort); do
+    class=$(echo "$file" | sed 's/\.class$//; s|/|.|g')
+    javap -J-Dfile.encoding=UTF-8 -v -p -classpath "$classes" "$class" \
+        | sed "s|$classes|/fixtures|" \
+        | sed '/\$default(/,/flags:/ s/(0x0009) ACC_PUBLIC, ACC_STATIC$/(0x1009) ACC_PUBLIC, ACC_STATIC, ACC_SYNTHETIC/' \
+        > "javap/$class.txt"
+done
+rm -rf "$classes"
@@ -0,0 +1,101 @@
+package com.fixture;
+
+import java.util.function.IntSupplier;
+
+// What kotlinc emits for:
+//
+//     class Account(var balance: Int) {
+//         val isOpen: Boolean get() = true
+//         fun deposit(amount: Int = 1) { balance += amount }
+//         fun getAmount(): Int = balance
+//         internal fun reset() { balance = 0 }
+//         fun audit(): IntSupplier = IntSupplier { balance }
+//         companion object {
+//             @JvmStatic fun open(): Account = Account(0)
+//             fun create(balance: Int): Account = Account(balance)
+//             val limit: Int get() = 100
+//         }
+//     }
+@kotlin.Metadata(
+    mv = {1, 9, 0},
+    k = 1,
+    xi = 48,
+    d1 = {"\000\012\012\004\010\001\030\001\012\002\010\024\010\006\030\000 \024B\004\022\002\020\002J\006\020\0132\002\020\015J\002\020\016J\002\020\017J\002\020\022R\005X\206$\020\002R\005X\206\010\020\010"},
+    d2 = {"Lcom/fixture/Account;", "", "balance", "", "(I)V", "getBalance", "()I", "setBalance",
+          "isOpen", "", "()Z", "deposit", "", "amount", "getAmount", "reset", "reset$fixture", "()V",
+          "audit", "Ljava/util/function/IntSupplier;", "Companion", "fixture"}
+)
+public final class Account {
+    public static final Companion Companion = new Companion();
+
+    private int balance;
+
+    public Account(int balance) {
+        this.balance = balance;
+    }
+
+    public final int getBalance() {
+        return balance;
+    }
+
+    public final void setBalance(int balance) {
+        this.balance = balance;
+    }
+
+    public final boolean isOpen() {
+        return true;
+    }
+
+    public final void deposit(int amount) {
+        balance += amount;
+    }
+
+    // Synthetic in the class file; capture.sh sets the flag.
+    public static void deposit$default(Account account, int amount, int mask, Object marker) {
+        if ((mask & 1) != 0) {
+            amount = 1;
+        }
+        account.deposit(amount);
+    }
+
+    public final int getAmount() {
+        return balance;
+    }
+
+    public final void reset$fixture() {
+        balance = 0;
+    }
+
+    public final IntSupplier audit() {
+        return () -> balance;
+    }
+
+    public static final Account open() {
+        return Companion.open();
+    }
+
+    @kotlin.Metadata(
+        mv = {1, 9, 0},
+        k = 1,
+        xi = 48,
+        d1 = {"\000\012\012\004\010\001\030\001\012\002\010\013\010\006\030\000J\002\020\007J\006\020\0112\002\020\012R\005X\206\010\020\003"},
+        d2 = {"Lcom/fixture/Account$Companion;", "", "()V", "limit", "", "getLimit", "()I", "open",
+              "Lcom/fixture/Account;", "create", "balance", "fixture"}
+    )
+    public static final class Companion {
+        private Companion() {
+        }
+
+        public final Account open() {
+            return new Account(0);
+        }
+
+        public final Account create(int balance) {
+            return new Account(balance);
+        }
+
+        public final int getLimit() {
+            return 100;
+        }
+    }
+}
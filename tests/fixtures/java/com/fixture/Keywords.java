@@ -0,0 +1,5 @@
+package com.fixture;
+
+// Components named after Rust keywords and the generated code's locals.
+public record Keywords(int type, String env, long obj, boolean self) {
+}
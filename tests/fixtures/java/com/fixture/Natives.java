@@ -0,0 +1,18 @@
+package com.fixture;
+
+import java.util.function.IntSupplier;
+
+public class Natives implements Comparable<Natives> {
+    public static native int add(int a, int b);
+
+    public native String describe(long handle);
+
+    public IntSupplier counter() {
+        return () -> 42;
+    }
+
+    @Override
+    public int compareTo(Natives other) {
+        return 0;
+    }
+}
@@ -0,0 +1,17 @@
+package com.fixture;
+
+public sealed interface Shape permits Shape.Circle, Shape.Square {
+    double area();
+
+    record Circle(double radius) implements Shape {
+        public double area() {
+            return Math.PI * radius * radius;
+        }
+    }
+
+    record Square(double side, String label) implements Shape {
+        public double area() {
+            return side * side;
+        }
+    }
+}
@@ -0,0 +1,25 @@
+package com.fixture;
+
+// What kotlinc emits for StringUtils.kt:
+//
+//     fun shout(text: String): String = text.uppercase()
+//     fun count(text: String, c: Char): Int = text.count { it == c }
+@kotlin.Metadata(
+    mv = {1, 9, 0},
+    k = 2,
+    xi = 48,
+    d1 = {"\000\004\012\002\010\010\032\006\020\0002\002\020\002\032\012\020\0032\002\020\0022\002\020\005"},
+    d2 = {"shout", "", "text", "count", "", "c", "", "fixture"}
+)
+public final class StringUtilsKt {
+    private StringUtilsKt() {
+    }
+
+    public static final String shout(String text) {
+        return text.toUpperCase();
+    }
+
+    public static final int count(String text, char c) {
+        return (int) text.chars().filter(x -> x == c).count();
+    }
+}
@@ -0,0 +1,25 @@
+package com.fixture;
+
+import java.util.List;
+
+public class Varargs {
+    public Varargs(String... names) {}
+
+    public static String join(String separator, String... parts) {
+        return String.join(separator, parts);
+    }
+
+    public int sum(int... values) {
+        int sum = 0;
+        for (int value : values) {
+            sum += value;
+        }
+        return sum;
+    }
+
+    public void take(String[] names) {}
+
+    public List<String> names() {
+        return List.of();
+    }
+}
@@ -0,0 +1,38 @@
+//! Record bindings for components named after Rust keywords and after the
+//! generated code's own locals compile and round-trip.
+//!
+//! `fixtures/bindings/keywords.rs` is kept up to date by a test in
+//! `codegen` (run with `--features build`); regenerate it with
+//! `auto_jni::generate_bindings_file` over the compiled `Keywords.java`,
+//! with `-Djava.class.path=target/auto-jni-fixtures` as the JVM option.
+
+use std::process::Command;
+
+#[allow(non_snake_case, non_camel_case_types, dead_code, unused_imports, clippy::all)]
+mod bindings {
+    include!("fixtures/bindings/keywords.rs");
+}
+
+use bindings::com_fixture_Keywords_Record;
+
+#[test]
+fn test_keyword_record_round_trip() {
+    // The class path in the bindings' JVM_OPTIONS.
+    let status = Command::new("javac")
+        .args(["-d", "target/auto-jni-fixtures", "tests/fixtures/java/com/fixture/Keywords.java"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let record = com_fixture_Keywords_Record {
+        r#type: 7,
+        env: "env".to_string(),
+        obj: 1 << 40,
+        self_: true,
+    };
+    let keywords = record.to_java().unwrap();
+    assert_eq!(keywords.r#type().unwrap(), 7);
+    assert_eq!(keywords.obj().unwrap(), 1 << 40);
+    assert!(keywords.self_().unwrap());
+    assert_eq!(keywords.to_record().unwrap(), record);
+}